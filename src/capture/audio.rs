@@ -0,0 +1,418 @@
+//! Audio capture for recordings
+//!
+//! System audio (WASAPI loopback on Windows, a PulseAudio/PipeWire monitor
+//! source on Linux) and the microphone are each written to a float WAV track
+//! next to the video, starting at the first captured frame like the event log
+//! does. When the recording finishes the tracks are mixed and muxed into the
+//! MP4 with the `ffmpeg` CLI; if that isn't available the WAV files are kept
+//! so nothing is lost.
+
+use super::clock::capture_epoch;
+use super::ffmpeg::{run_ffmpeg, temp_path};
+use super::recorder::{is_paused, paused_total};
+use super::source::CaptureError;
+use crate::config::AudioMode;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// Writes interleaved 32-bit float samples to a WAV file
+struct WavWriter {
+    file: BufWriter<File>,
+    data_bytes: u32,
+    channels: u16,
+}
+
+impl WavWriter {
+    fn create(path: &Path, sample_rate: u32, channels: u16) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let block_align = channels * 4;
+
+        // RIFF/WAVE header with IEEE float format; sizes are patched in `finish`
+        file.write_all(b"RIFF")?;
+        file.write_all(&0u32.to_le_bytes())?;
+        file.write_all(b"WAVEfmt ")?;
+        file.write_all(&16u32.to_le_bytes())?;
+        file.write_all(&3u16.to_le_bytes())?;
+        file.write_all(&channels.to_le_bytes())?;
+        file.write_all(&sample_rate.to_le_bytes())?;
+        file.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        file.write_all(&block_align.to_le_bytes())?;
+        file.write_all(&32u16.to_le_bytes())?;
+        file.write_all(b"data")?;
+        file.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            file,
+            data_bytes: 0,
+            channels,
+        })
+    }
+
+    /// Samples written so far, across all channels
+    fn samples(&self) -> u64 {
+        self.data_bytes as u64 / 4
+    }
+
+    /// Pad the track with silence up to `samples`, in whole frames
+    fn fill_silence_until(&mut self, samples: u64) -> std::io::Result<()> {
+        let channels = self.channels as u64;
+        let missing = samples.saturating_sub(self.samples()) / channels * channels;
+        self.write((0..missing).map(|_| 0.0))
+    }
+
+    fn write(&mut self, samples: impl Iterator<Item = f32>) -> std::io::Result<()> {
+        for sample in samples {
+            self.file.write_all(&sample.to_le_bytes())?;
+            self.data_bytes = self.data_bytes.saturating_add(4);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file
+            .write_all(&(36u32.saturating_add(self.data_bytes)).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(40))?;
+        self.file.write_all(&self.data_bytes.to_le_bytes())?;
+        self.file.flush()
+    }
+}
+
+/// Size of the header written by [`WavWriter::create`]
+const WAV_HEADER_BYTES: u64 = 44;
+
+/// Patch the size fields of a WAV track that was never finished
+fn repair_wav_header(path: &Path) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let len = file.metadata()?.len();
+    // Whole samples only; a crash can cut the last one short
+    let data_bytes =
+        (len.saturating_sub(WAV_HEADER_BYTES) / 4 * 4).min((u32::MAX - 36) as u64) as u32;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&(36 + data_bytes).to_le_bytes())?;
+    file.seek(SeekFrom::Start(40))?;
+    file.write_all(&data_bytes.to_le_bytes())?;
+    Ok(())
+}
+
+/// An audio input to record
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AudioInput {
+    System,
+    Microphone,
+}
+
+impl AudioInput {
+    fn for_mode(mode: AudioMode) -> &'static [AudioInput] {
+        match mode {
+            AudioMode::None => &[],
+            AudioMode::System => &[AudioInput::System],
+            AudioMode::Microphone => &[AudioInput::Microphone],
+            AudioMode::Both => &[AudioInput::System, AudioInput::Microphone],
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AudioInput::System => "system",
+            AudioInput::Microphone => "mic",
+        }
+    }
+
+    /// Device and stream config to read this input from
+    fn device(
+        self,
+        host: &cpal::Host,
+    ) -> Result<(cpal::Device, cpal::SupportedStreamConfig), CaptureError> {
+        match self {
+            AudioInput::Microphone => {
+                let device = host.default_input_device().ok_or("No microphone found")?;
+                let config = device.default_input_config()?;
+                Ok((device, config))
+            }
+            // WASAPI records what an output device plays when it is opened as an input
+            #[cfg(windows)]
+            AudioInput::System => {
+                let device = host
+                    .default_output_device()
+                    .ok_or("No audio output device found")?;
+                let config = device.default_output_config()?;
+                Ok((device, config))
+            }
+            // PulseAudio and PipeWire expose what's playing as "monitor" inputs
+            #[cfg(not(windows))]
+            AudioInput::System => {
+                let device = host
+                    .input_devices()?
+                    .find(|d| d.name().is_ok_and(|n| n.to_lowercase().contains("monitor")))
+                    .ok_or("No monitor input found for system audio")?;
+                let config = device.default_input_config()?;
+                Ok((device, config))
+            }
+        }
+    }
+}
+
+/// Build an input stream converting `T` samples to f32 and appending them to `writer`
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    writer: Arc<Mutex<WavWriter>>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as u64;
+    let samples_per_sec = config.sample_rate.0 as f64 * channels as f64;
+    let max_gap = (samples_per_sec / 10.0) as u64;
+
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            // The video (and the event log) start at the first captured
+            // frame; audio from before it is dropped
+            let Some(epoch) = capture_epoch() else {
+                return;
+            };
+            // Paused audio is cut like paused video
+            if is_paused() {
+                return;
+            }
+            let mut writer = writer.lock().unwrap();
+
+            let recorded = epoch.elapsed().saturating_sub(paused_total());
+            let recorded_samples = (recorded.as_secs_f64() * samples_per_sec) as u64;
+            // The first buffer can reach back past the first frame
+            let data = if writer.samples() == 0 {
+                let early = (data.len() as u64).saturating_sub(recorded_samples) / channels;
+                &data[(early * channels) as usize..]
+            } else {
+                data
+            };
+
+            // WASAPI loopback delivers nothing while nothing is playing; fill
+            // such gaps with silence so the track stays in sync with the video
+            let due = recorded_samples.saturating_sub(data.len() as u64);
+            if due > writer.samples() + max_gap {
+                if let Err(e) = writer.fill_silence_until(due) {
+                    eprintln!("Audio write error: {e}");
+                }
+            }
+            if let Err(e) = writer.write(data.iter().map(|&s| f32::from_sample(s))) {
+                eprintln!("Audio write error: {e}");
+            }
+        },
+        |e| eprintln!("Audio stream error: {e}"),
+        None,
+    )
+}
+
+/// Open `input` and start recording it into a WAV file at `path`
+fn start_input(
+    host: &cpal::Host,
+    input: AudioInput,
+    path: &Path,
+) -> Result<(cpal::Stream, Arc<Mutex<WavWriter>>), CaptureError> {
+    let (device, supported) = input.device(host)?;
+    let config: cpal::StreamConfig = supported.config();
+    let writer = Arc::new(Mutex::new(WavWriter::create(
+        path,
+        config.sample_rate.0,
+        config.channels,
+    )?));
+
+    let stream = match supported.sample_format() {
+        SampleFormat::I8 => build_stream::<i8>(&device, &config, writer.clone()),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, writer.clone()),
+        SampleFormat::I32 => build_stream::<i32>(&device, &config, writer.clone()),
+        SampleFormat::U8 => build_stream::<u8>(&device, &config, writer.clone()),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, writer.clone()),
+        SampleFormat::U32 => build_stream::<u32>(&device, &config, writer.clone()),
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, writer.clone()),
+        SampleFormat::F64 => build_stream::<f64>(&device, &config, writer.clone()),
+        other => return Err(format!("Unsupported audio sample format {other}").into()),
+    }?;
+    stream.play()?;
+
+    println!(
+        "Recording {} audio from \"{}\" ({} Hz, {} ch)",
+        input.label(),
+        device.name().unwrap_or_default(),
+        config.sample_rate.0,
+        config.channels
+    );
+    Ok((stream, writer))
+}
+
+/// Records the configured audio inputs into WAV tracks until finished
+///
+/// cpal streams aren't `Send` on every platform, so they live on their own
+/// thread; this handle only holds the channel that ends it.
+pub struct AudioRecorder {
+    stop_tx: mpsc::Sender<()>,
+    thread: JoinHandle<Vec<PathBuf>>,
+}
+
+impl AudioRecorder {
+    /// Start recording the inputs for `mode`, with tracks named after `video_path`
+    ///
+    /// Returns `None` for [`AudioMode::None`] or if no input could be opened.
+    /// An input that fails to open is skipped with a warning.
+    pub fn start(mode: AudioMode, video_path: &Path) -> Option<Self> {
+        let inputs = AudioInput::for_mode(mode);
+        if inputs.is_empty() {
+            return None;
+        }
+
+        let video_path = video_path.to_path_buf();
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let thread = std::thread::spawn(move || {
+            let host = cpal::default_host();
+            let mut tracks = Vec::new();
+            for &input in inputs {
+                let path = video_path.with_extension(format!("{}.wav", input.label()));
+                match start_input(&host, input, &path) {
+                    Ok((stream, writer)) => tracks.push((stream, writer, path)),
+                    Err(e) => eprintln!("Skipping {} audio: {e}", input.label()),
+                }
+            }
+            let _ = ready_tx.send(!tracks.is_empty());
+
+            // Record until stopped (or the handle is dropped)
+            let _ = stop_rx.recv();
+
+            tracks
+                .into_iter()
+                .filter_map(|(stream, writer, path)| {
+                    drop(stream);
+                    match writer.lock().unwrap().finish() {
+                        Ok(()) => Some(path),
+                        Err(e) => {
+                            eprintln!("Failed to finish audio track {path:?}: {e}");
+                            None
+                        }
+                    }
+                })
+                .collect()
+        });
+
+        if ready_rx.recv().unwrap_or(false) {
+            Some(Self { stop_tx, thread })
+        } else {
+            let _ = stop_tx.send(());
+            let _ = thread.join();
+            None
+        }
+    }
+
+    /// Stop recording and return the finished WAV tracks
+    pub fn finish(self) -> Vec<PathBuf> {
+        let _ = self.stop_tx.send(());
+        self.thread.join().unwrap_or_default()
+    }
+}
+
+/// WAV tracks left next to `video` by a recording that never finished
+///
+/// Their headers are repaired so they can be muxed like finished tracks.
+pub fn recover_tracks(video: &Path) -> Vec<PathBuf> {
+    [AudioInput::System, AudioInput::Microphone]
+        .into_iter()
+        .map(|input| video.with_extension(format!("{}.wav", input.label())))
+        .filter(|path| path.exists())
+        .filter(|path| match repair_wav_header(path) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Could not repair audio track {path:?}: {e}");
+                false
+            }
+        })
+        .collect()
+}
+
+/// Mix `tracks` and mux them into `video` in place
+///
+/// The WAV tracks are deleted once muxed; on failure they are left next to
+/// the video.
+pub fn mux_audio(video: &Path, tracks: &[PathBuf]) -> Result<(), CaptureError> {
+    if tracks.is_empty() {
+        return Ok(());
+    }
+
+    let muxed = temp_path(video, "muxing");
+    let mut args: Vec<&std::ffi::OsStr> = vec!["-i".as_ref(), video.as_os_str()];
+    for track in tracks {
+        args.extend(["-i".as_ref(), track.as_os_str()]);
+    }
+    // Pad the mix with silence so `-shortest` always ends at the last video frame
+    let mix = format!(
+        "{}amix=inputs={}:duration=longest:normalize=0,apad[a]",
+        (1..=tracks.len())
+            .map(|i| format!("[{i}:a]"))
+            .collect::<String>(),
+        tracks.len()
+    );
+    args.extend([
+        "-filter_complex".as_ref(),
+        mix.as_ref(),
+        "-map".as_ref(),
+        "0:v".as_ref(),
+        "-map".as_ref(),
+        "[a]".as_ref(),
+        "-c:v".as_ref(),
+        "copy".as_ref(),
+        "-c:a".as_ref(),
+        "aac".as_ref(),
+        "-b:a".as_ref(),
+        "192k".as_ref(),
+        "-shortest".as_ref(),
+        muxed.as_os_str(),
+    ]);
+
+    if let Err(e) = run_ffmpeg(&args) {
+        let _ = std::fs::remove_file(&muxed);
+        return Err(e);
+    }
+    std::fs::rename(&muxed, video)?;
+    for track in tracks {
+        let _ = std::fs::remove_file(track);
+    }
+    Ok(())
+}
+
+/// Copy the audio of `source` (if it has any) into `video` in place
+///
+/// Used after re-encoding a recording, since the video encoder drops audio.
+pub fn copy_audio(source: &Path, video: &Path) -> Result<(), CaptureError> {
+    let muxed = temp_path(video, "muxing");
+    let args: [&std::ffi::OsStr; 11] = [
+        "-i".as_ref(),
+        video.as_os_str(),
+        "-i".as_ref(),
+        source.as_os_str(),
+        "-map".as_ref(),
+        "0:v".as_ref(),
+        // Optional mapping: recordings without audio just get the video back
+        "-map".as_ref(),
+        "1:a?".as_ref(),
+        "-c".as_ref(),
+        "copy".as_ref(),
+        muxed.as_os_str(),
+    ];
+
+    if let Err(e) = run_ffmpeg(&args) {
+        let _ = std::fs::remove_file(&muxed);
+        return Err(e);
+    }
+    std::fs::rename(&muxed, video)?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Monotonic clock shared by capture and input sampling
+//!
+//! Capture sources timestamp frames from their first frame. The sink marks
+//! the moment that corresponds to timestamp zero here, so the input sampler
+//! can put events on exactly the same timeline as the video.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Epoch not marked yet
+const UNSET: u64 = u64::MAX;
+
+/// Nanoseconds after [`base`] of frame timestamp zero
+static CAPTURE_EPOCH_NS: AtomicU64 = AtomicU64::new(UNSET);
+
+/// Process-wide reference point; only differences to it are stored
+fn base() -> Instant {
+    static BASE: OnceLock<Instant> = OnceLock::new();
+    *BASE.get_or_init(Instant::now)
+}
+
+/// Forget the epoch of the previous capture
+pub fn reset_capture_epoch() {
+    // Pin the reference point before any epoch is measured against it
+    base();
+    CAPTURE_EPOCH_NS.store(UNSET, Ordering::SeqCst);
+}
+
+/// Mark the epoch from a frame that arrived just now with `timestamp`
+///
+/// Only the first call after [`reset_capture_epoch`] counts.
+pub fn mark_capture_epoch(timestamp: Duration) {
+    let epoch = base().elapsed().saturating_sub(timestamp).as_nanos() as u64;
+    let _ = CAPTURE_EPOCH_NS.compare_exchange(UNSET, epoch, Ordering::SeqCst, Ordering::SeqCst);
+}
+
+/// The instant frame timestamps count from, once the first frame has arrived
+pub fn capture_epoch() -> Option<Instant> {
+    match CAPTURE_EPOCH_NS.load(Ordering::SeqCst) {
+        UNSET => None,
+        ns => Some(base() + Duration::from_nanos(ns)),
+    }
+}
@@ -0,0 +1,239 @@
+//! Helpers around the `ffmpeg` command-line tool
+//!
+//! Used for the jobs the in-process encoders can't do: muxing audio,
+//! joining recorded segments without re-encoding, checking that a file
+//! left behind by a crash is playable, cutting recordings apart and adding
+//! chapters.
+
+use super::source::CaptureError;
+use crate::zoom::Chapter;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Run `ffmpeg` with `args`, failing if it is missing or exits with an error
+pub fn run_ffmpeg(args: &[&OsStr]) -> Result<(), CaptureError> {
+    let output = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-y"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("Could not run ffmpeg: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Whether `ffmpeg` can be run, checked once per process
+pub fn ffmpeg_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new("ffmpeg")
+            .arg("-version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// Whether `video` is a finished file `ffprobe` can read
+///
+/// A segment that was still being written when the app died has no index
+/// and fails this check.
+pub fn is_complete(video: &Path) -> bool {
+    Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(video)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Length of `video` in milliseconds, from `ffprobe`
+pub fn probe_duration_ms(video: &Path) -> Option<u64> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(video)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let secs: f64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some((secs * 1000.0).round() as u64)
+}
+
+/// Path next to `video` for an intermediate file, keeping the `.mp4` extension
+pub fn temp_path(video: &Path, tag: &str) -> PathBuf {
+    video.with_extension(format!("{tag}.mp4"))
+}
+
+/// Join MP4 `segments` end to end into `output` without re-encoding
+///
+/// Every segment must come from the same encoder settings, which is the case
+/// for segments written by one [`super::segments::SegmentWriter`].
+pub fn concat_segments(segments: &[PathBuf], output: &Path) -> Result<(), CaptureError> {
+    if segments.is_empty() {
+        return Err("No segments to join".into());
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // The concat demuxer reads its inputs from a list file
+    let list_path = output.with_extension("segments.txt");
+    {
+        let mut list = std::fs::File::create(&list_path)?;
+        for segment in segments {
+            let absolute = std::path::absolute(segment)?;
+            // Quote for the concat syntax: ' becomes '\''
+            let escaped = absolute.to_string_lossy().replace('\'', "'\\''");
+            writeln!(list, "file '{escaped}'")?;
+        }
+    }
+
+    let joining = temp_path(output, "joining");
+    let args: [&OsStr; 9] = [
+        "-f".as_ref(),
+        "concat".as_ref(),
+        "-safe".as_ref(),
+        "0".as_ref(),
+        "-i".as_ref(),
+        list_path.as_os_str(),
+        "-c".as_ref(),
+        "copy".as_ref(),
+        joining.as_os_str(),
+    ];
+    let result = run_ffmpeg(&args);
+    let _ = std::fs::remove_file(&list_path);
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&joining);
+        return Err(e);
+    }
+    std::fs::rename(&joining, output)?;
+    Ok(())
+}
+
+/// Re-encode the part of `video` from `from_ms` to `to_ms` into `output`
+///
+/// Re-encoding rather than copying lets the cut land on any frame instead of
+/// the nearest keyframe.
+pub fn cut_video(
+    video: &Path,
+    from_ms: u64,
+    to_ms: u64,
+    output: &Path,
+) -> Result<(), CaptureError> {
+    let start = format!("{:.3}", from_ms as f64 / 1000.0);
+    let length = format!("{:.3}", to_ms.saturating_sub(from_ms) as f64 / 1000.0);
+    let cutting = temp_path(output, "cutting");
+    let args: [&OsStr; 21] = [
+        "-ss".as_ref(),
+        start.as_ref(),
+        "-i".as_ref(),
+        video.as_os_str(),
+        "-t".as_ref(),
+        length.as_ref(),
+        "-map".as_ref(),
+        "0:v".as_ref(),
+        "-map".as_ref(),
+        "0:a?".as_ref(),
+        "-c:v".as_ref(),
+        "libx264".as_ref(),
+        "-preset".as_ref(),
+        "veryfast".as_ref(),
+        "-crf".as_ref(),
+        "18".as_ref(),
+        "-pix_fmt".as_ref(),
+        "yuv420p".as_ref(),
+        "-c:a".as_ref(),
+        "aac".as_ref(),
+        cutting.as_os_str(),
+    ];
+    if let Err(e) = run_ffmpeg(&args) {
+        let _ = std::fs::remove_file(&cutting);
+        return Err(e);
+    }
+    std::fs::rename(&cutting, output)?;
+    Ok(())
+}
+
+/// Embed `chapters` in `video` as chapter metadata, without re-encoding
+pub fn add_chapters(video: &Path, chapters: &[Chapter]) -> Result<(), CaptureError> {
+    // ffmpeg reads chapters from an FFMETADATA file
+    let metadata_path = video.with_extension("chapters.txt");
+    {
+        let mut metadata = std::fs::File::create(&metadata_path)?;
+        writeln!(metadata, ";FFMETADATA1")?;
+        for chapter in chapters {
+            writeln!(metadata, "[CHAPTER]")?;
+            writeln!(metadata, "TIMEBASE=1/1000")?;
+            writeln!(metadata, "START={}", chapter.start_ms)?;
+            writeln!(metadata, "END={}", chapter.end_ms)?;
+            writeln!(metadata, "title={}", escape_metadata(&chapter.title))?;
+        }
+    }
+
+    let tagged = temp_path(video, "chapters");
+    let args: [&OsStr; 13] = [
+        "-i".as_ref(),
+        video.as_os_str(),
+        "-i".as_ref(),
+        metadata_path.as_os_str(),
+        "-map".as_ref(),
+        "0".as_ref(),
+        "-map_metadata".as_ref(),
+        "0".as_ref(),
+        "-map_chapters".as_ref(),
+        "1".as_ref(),
+        "-c".as_ref(),
+        "copy".as_ref(),
+        tagged.as_os_str(),
+    ];
+    let result = run_ffmpeg(&args);
+    let _ = std::fs::remove_file(&metadata_path);
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tagged);
+        return Err(e);
+    }
+    std::fs::rename(&tagged, video)?;
+    Ok(())
+}
+
+/// Escape `=`, `;`, `#`, `\` and line breaks for an FFMETADATA value
+fn escape_metadata(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
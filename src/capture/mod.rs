@@ -1,3 +1,26 @@
+pub mod audio;
+pub mod clock;
+pub mod ffmpeg;
 pub mod recorder;
+pub mod recovery;
+pub mod replay;
+pub mod segments;
+pub mod source;
+pub mod split;
+pub mod stats;
+pub mod synthetic;
+#[cfg(windows)]
+pub mod windows;
+pub mod writer;
+#[cfg(target_os = "linux")]
+pub mod x11;
 
 pub use recorder::*;
+pub use recovery::recover_sessions;
+pub use replay::{
+    request_replay_save, start_replay, stop_replay, take_replay_saved, REPLAY_SEGMENT_SECS,
+};
+pub use source::{
+    list_monitors, list_windows, scale_factor_at, CaptureArea, ForegroundWatcher, WindowInfo,
+};
+pub use split::split_at_markers;
@@ -1,27 +1,28 @@
-//! Screen recording using windows-capture crate
+//! Recording pipeline: capture source -> video writer
 //!
-//! Uses the Windows Graphics Capture API for high-performance screen capture
-//! with built-in video encoding.
-
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
-
-#[cfg(windows)]
-use windows_capture::{
-    capture::{Context, GraphicsCaptureApiHandler},
-    encoder::{
-        AudioSettingsBuilder, ContainerSettingsBuilder, VideoEncoder, VideoSettingsBuilder,
-        VideoSettingsSubType,
-    },
-    frame::Frame,
-    graphics_capture_api::InternalCaptureControl,
-    monitor::Monitor,
-    settings::{
-        ColorFormat, CursorCaptureSettings, DirtyRegionSettings, DrawBorderSettings,
-        MinimumUpdateIntervalSettings, SecondaryWindowSettings, Settings,
-    },
+//! Frames come from the platform [`CaptureSource`] (Windows Graphics Capture,
+//! X11, or a synthetic generator) and are encoded to MP4 by [`VideoWriter`].
+//! Pausing drops frames and shifts later timestamps back by the paused time,
+//! so one file holds a continuous timeline.
+//!
+//! When `ffmpeg` is available the video is written as short segments in a
+//! `.parts` folder and joined when the recording stops, so a crash loses at
+//! most the last segment (see [`super::recovery`]).
+
+use super::audio::{mux_audio, AudioRecorder};
+use super::clock::{mark_capture_epoch, reset_capture_epoch};
+use super::ffmpeg::{concat_segments, ffmpeg_available};
+use super::segments::SegmentWriter;
+use super::source::{
+    create_source, CaptureArea, CaptureError, CaptureSource, CapturedFrame, FrameSink,
 };
+use super::stats::{stats_path, CaptureStats};
+use super::writer::VideoWriter;
+use crate::config::{AudioMode, CaptureTarget, Config};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 /// Global recording state
 static IS_RECORDING: AtomicBool = AtomicBool::new(false);
@@ -36,191 +37,417 @@ pub fn set_recording(state: bool) {
     IS_RECORDING.store(state, Ordering::SeqCst);
 }
 
+/// Wall-clock pause bookkeeping for the current recording
+///
+/// Kept by wall clock rather than frame timestamps because capture sources
+/// may deliver no frames at all while the screen is static.
+struct PauseClock {
+    paused_at: Option<Instant>,
+    total: Duration,
+}
+
+static PAUSE_CLOCK: Mutex<PauseClock> = Mutex::new(PauseClock {
+    paused_at: None,
+    total: Duration::ZERO,
+});
+
+/// Check if the current recording is paused
+pub fn is_paused() -> bool {
+    PAUSE_CLOCK.lock().unwrap().paused_at.is_some()
+}
+
+/// Pause or resume the current recording
+pub fn set_paused(state: bool) {
+    let mut clock = PAUSE_CLOCK.lock().unwrap();
+    match (state, clock.paused_at) {
+        (true, None) => clock.paused_at = Some(Instant::now()),
+        (false, Some(paused_at)) => {
+            clock.total += paused_at.elapsed();
+            clock.paused_at = None;
+        }
+        _ => {}
+    }
+}
+
+/// Time spent in completed pauses of the current recording
+pub fn paused_total() -> Duration {
+    PAUSE_CLOCK.lock().unwrap().total
+}
+
 /// Recorder configuration
 #[derive(Clone, Debug)]
 pub struct RecorderConfig {
     pub output_path: PathBuf,
     pub events_path: PathBuf,
+    /// Holds the segments and event journal until the recording is finished
+    pub parts_dir: PathBuf,
     pub width: u32,
     pub height: u32,
     pub fps: u32,
+    pub target: CaptureTarget,
+    /// Stop the recording after this long; `None` records until stopped
+    pub max_duration_secs: Option<u64>,
+    pub audio_mode: AudioMode,
 }
 
 impl RecorderConfig {
-    /// Create a new config with timestamped filenames
-    pub fn with_timestamp() -> Self {
-        let output_dir = dirs::video_dir()
-            .unwrap_or_else(|| dirs::home_dir().unwrap_or_default())
-            .join("DemoRecorder");
-
-        // Generate timestamp for filename
-        let now = std::time::SystemTime::now();
-        let timestamp = now
+    /// Build a config from the user's settings, with filenames for a recording starting now
+    pub fn from_config(config: &Config) -> Self {
+        let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
-
-        // Format: recording_YYYYMMDD_HHMMSS
-        let filename = format!("recording_{}.mp4", timestamp);
-        let events_filename = format!("recording_{}.events.json", timestamp);
+        let stem = config.recording_stem(timestamp);
 
         Self {
-            output_path: output_dir.join(&filename),
-            events_path: output_dir.join(&events_filename),
+            output_path: config.output_folder.join(format!("{stem}.mp4")),
+            events_path: config
+                .output_folder
+                .join(format!("{stem}.{}", crate::zoom::EVENT_LOG_EXTENSION)),
+            parts_dir: config.output_folder.join(format!("{stem}.parts")),
             width: 1920,
             height: 1080,
-            fps: 30,
+            fps: config.fps.clamp(1, 240),
+            target: config.capture_target.clone(),
+            max_duration_secs: Some(config.max_duration_secs).filter(|&secs| secs > 0),
+            audio_mode: config.audio_mode,
         }
     }
 }
 
-impl Default for RecorderConfig {
-    fn default() -> Self {
-        Self::with_timestamp()
-    }
-}
-
-#[cfg(windows)]
-pub mod windows_impl {
-    use super::*;
+/// How long before the max duration cutoff to warn
+const DURATION_WARNING_SECS: u64 = 30;
 
-    /// Capture handler that encodes frames to video
-    pub struct CaptureHandler {
-        encoder: Option<VideoEncoder>,
-        start: Instant,
-        max_duration_secs: u64,
-    }
+/// Set once per recording when the max duration cutoff is close
+static DURATION_WARNING: AtomicBool = AtomicBool::new(false);
 
-    impl GraphicsCaptureApiHandler for CaptureHandler {
-        type Flags = RecorderConfig;
-        type Error = Box<dyn std::error::Error + Send + Sync>;
+/// Check if the recording is about to hit its max duration and clear the flag
+pub fn take_duration_warning() -> bool {
+    DURATION_WARNING.swap(false, Ordering::SeqCst)
+}
 
-        fn new(ctx: Context<Self::Flags>) -> Result<Self, Self::Error> {
-            let config = ctx.flags;
+/// Length of each segment of a segmented recording
+const RECORDING_SEGMENT_SECS: u64 = 10;
 
-            // Create output directory
-            if let Some(parent) = config.output_path.parent() {
-                std::fs::create_dir_all(parent)?;
-            }
+/// Where the frames of a recording go
+enum VideoOutput {
+    /// Straight into the output file (no `ffmpeg` to join segments)
+    Single(VideoWriter),
+    /// Into segments that are joined into the output file at the end
+    Segments(SegmentWriter),
+}
 
-            let encoder = VideoEncoder::new(
-                VideoSettingsBuilder::new(config.width, config.height)
-                    .sub_type(VideoSettingsSubType::H264) // H.264 for WMP compatibility
-                    .frame_rate(config.fps),
-                AudioSettingsBuilder::default().disabled(true),
-                ContainerSettingsBuilder::default(),
-                &config.output_path,
-            )?;
-
-            println!("Recording to: {:?}", config.output_path);
-
-            Ok(Self {
-                encoder: Some(encoder),
-                start: Instant::now(),
-                max_duration_secs: 300, // 5 minutes max
-            })
+impl VideoOutput {
+    fn write(&mut self, frame: &CapturedFrame<'_>, pts: Duration) -> Result<(), CaptureError> {
+        match self {
+            VideoOutput::Single(writer) => writer.write(frame, pts),
+            VideoOutput::Segments(segments) => segments.write(frame, pts),
         }
+    }
 
-        fn on_frame_arrived(
-            &mut self,
-            frame: &mut Frame,
-            capture_control: InternalCaptureControl,
-        ) -> Result<(), Self::Error> {
-            // Check if we should stop
-            if !is_recording() || self.start.elapsed().as_secs() >= self.max_duration_secs {
-                if let Some(encoder) = self.encoder.take() {
-                    encoder.finish()?;
-                    println!("\nRecording saved!");
-                }
-                capture_control.stop();
-                set_recording(false);
-                return Ok(());
+    /// Finish encoding so `output_path` holds the whole recording
+    fn finish(self, output_path: &Path) -> Result<(), CaptureError> {
+        match self {
+            VideoOutput::Single(writer) => writer.finish(),
+            VideoOutput::Segments(segments) => {
+                let paths: Vec<PathBuf> = segments.finish()?.into_iter().map(|s| s.path).collect();
+                concat_segments(&paths, output_path)
             }
+        }
+    }
+}
 
-            // Send frame to encoder
-            if let Some(ref mut encoder) = self.encoder {
-                encoder.send_frame(frame)?;
-            }
+/// Source of the recording in progress, kept so it can be stopped
+static ACTIVE_SOURCE: Mutex<Option<Box<dyn CaptureSource>>> = Mutex::new(None);
+
+/// Incremented per recording so a late-finishing sink can't clear a newer session
+static RECORDING_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// Frame sink that encodes captured frames to the output file
+struct RecordingSink {
+    session: u64,
+    writer: Option<VideoOutput>,
+    audio: Option<AudioRecorder>,
+    output_path: PathBuf,
+    parts_dir: PathBuf,
+    max_duration_secs: Option<u64>,
+    warned: bool,
+    last_printed_secs: u64,
+    /// Timestamp of the last encoded frame, to keep output timestamps increasing
+    last_pts: Option<Duration>,
+    stats: CaptureStats,
+}
+
+impl FrameSink for RecordingSink {
+    fn on_frame(&mut self, frame: CapturedFrame<'_>) -> Result<bool, CaptureError> {
+        // Check if we should stop
+        let is_current = RECORDING_SESSION.load(Ordering::SeqCst) == self.session;
+        if !is_current || !is_recording() {
+            return Ok(false);
+        }
+        mark_capture_epoch(frame.timestamp);
 
-            // Print progress every second
-            let elapsed = self.start.elapsed().as_secs();
-            if elapsed > 0 && self.start.elapsed().subsec_millis() < 50 {
-                print!("\rRecording: {}s", elapsed);
-                std::io::Write::flush(&mut std::io::stdout())?;
+        // Drop frames while paused and cut the paused time from later
+        // timestamps, so the video continues where it left off without a frozen gap
+        if is_paused() {
+            return Ok(true);
+        }
+        let pts = frame.timestamp.saturating_sub(paused_total());
+        if self.last_pts.is_some_and(|last| pts <= last) {
+            // Captured just before resuming; would land before the previous frame
+            self.stats.record_duplicate();
+            return Ok(true);
+        }
+        if let Some(max_secs) = self.max_duration_secs {
+            if pts.as_secs() >= max_secs {
+                println!("\nMax duration of {max_secs}s reached, stopping");
+                return Ok(false);
             }
+            if !self.warned && pts.as_secs() + DURATION_WARNING_SECS >= max_secs {
+                self.warned = true;
+                DURATION_WARNING.store(true, Ordering::SeqCst);
+                println!(
+                    "\nRecording stops in {}s (max duration {max_secs}s)",
+                    max_secs - pts.as_secs()
+                );
+            }
+        }
+        self.last_pts = Some(pts);
+        self.stats.record_frame(pts);
 
-            Ok(())
+        // Send frame to encoder
+        if let Some(ref mut writer) = self.writer {
+            writer.write(&frame, pts)?;
         }
 
-        fn on_closed(&mut self) -> Result<(), Self::Error> {
-            println!("\nCapture session ended");
-            if let Some(encoder) = self.encoder.take() {
-                encoder.finish()?;
-            }
-            set_recording(false);
-            Ok(())
+        // Print progress every second
+        let elapsed = pts.as_secs();
+        if elapsed > self.last_printed_secs {
+            self.last_printed_secs = elapsed;
+            print!("\rRecording: {}s", elapsed);
+            std::io::Write::flush(&mut std::io::stdout())?;
         }
-    }
 
-    /// Start recording the primary monitor
-    pub fn start_recording(
-        mut config: RecorderConfig,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        set_recording(true);
-
-        let primary_monitor = Monitor::primary()?;
-
-        // Get actual monitor dimensions
-        let actual_width = primary_monitor.width()?;
-        let actual_height = primary_monitor.height()?;
-
-        // Update config with actual dimensions
-        config.width = actual_width;
-        config.height = actual_height;
-
-        println!("Monitor resolution: {}x{}", actual_width, actual_height);
-
-        let settings = Settings::new(
-            primary_monitor,
-            CursorCaptureSettings::Default,
-            DrawBorderSettings::WithoutBorder,
-            SecondaryWindowSettings::Default,
-            MinimumUpdateIntervalSettings::Default,
-            DirtyRegionSettings::Default,
-            ColorFormat::Rgba8,
-            config,
-        );
+        Ok(true)
+    }
 
-        // Start capture in a new thread
-        std::thread::spawn(move || {
-            if let Err(e) = CaptureHandler::start(settings) {
-                eprintln!("Capture error: {e}");
-                set_recording(false);
+    fn on_stopped(&mut self) -> Result<(), CaptureError> {
+        if RECORDING_SESSION.load(Ordering::SeqCst) == self.session {
+            set_recording(false);
+        }
+        // Stop audio first so the tracks are complete by the time the video is
+        let tracks = self.audio.take().map(AudioRecorder::finish);
+        if let Some(writer) = self.writer.take() {
+            if let Err(e) = writer.finish(&self.output_path) {
+                eprintln!(
+                    "\nCould not finish recording, partial files kept in {:?} for recovery",
+                    self.parts_dir
+                );
+                return Err(e);
             }
-        });
-
+            let _ = std::fs::remove_dir_all(&self.parts_dir);
+            println!(
+                "\n{} frames, {} dropped ({:.1}%), {} duplicated",
+                self.stats.frames,
+                self.stats.dropped_frames,
+                self.stats.drop_percent(),
+                self.stats.duplicate_frames
+            );
+            if let Err(e) = self.stats.save(&stats_path(&self.output_path)) {
+                eprintln!("Failed to save capture stats: {e}");
+            }
+            if let Some(tracks) = tracks {
+                match mux_audio(&self.output_path, &tracks) {
+                    Ok(()) => println!("\nAudio muxed ({} track(s))", tracks.len()),
+                    Err(e) => eprintln!("\nAudio kept as separate WAV files: {e}"),
+                }
+            }
+            println!("\nRecording saved!");
+        }
         Ok(())
     }
+}
+
+/// Record from an explicit source until [`stop_recording`] is called
+///
+/// `config.width`/`config.height` are overwritten with the source resolution.
+/// Returns the screen area being recorded.
+pub fn record_with_source(
+    mut source: Box<dyn CaptureSource>,
+    mut config: RecorderConfig,
+) -> Result<CaptureArea, CaptureError> {
+    // Stop whatever was left over from a previous session before starting
+    if let Some(mut previous) = ACTIVE_SOURCE.lock().unwrap().take() {
+        previous.stop();
+    }
 
-    /// Stop the current recording
-    pub fn stop_recording() {
+    let (width, height) = source.resolution();
+    config.width = width;
+    config.height = height;
+
+    std::fs::create_dir_all(&config.parts_dir)?;
+    let writer = if ffmpeg_available() {
+        VideoOutput::Segments(SegmentWriter::new(
+            &config.parts_dir,
+            width,
+            height,
+            config.fps,
+            Duration::from_secs(RECORDING_SEGMENT_SECS),
+        )?)
+    } else {
+        println!("ffmpeg not found, recording to a single file (not crash-safe)");
+        VideoOutput::Single(VideoWriter::new(
+            &config.output_path,
+            width,
+            height,
+            config.fps,
+        )?)
+    };
+    println!(
+        "Recording to: {:?} ({}x{} @ {} fps, {:?})",
+        config.output_path,
+        width,
+        height,
+        config.fps,
+        source.pixel_format()
+    );
+
+    let session = RECORDING_SESSION.fetch_add(1, Ordering::SeqCst) + 1;
+    *PAUSE_CLOCK.lock().unwrap() = PauseClock {
+        paused_at: None,
+        total: Duration::ZERO,
+    };
+    DURATION_WARNING.store(false, Ordering::SeqCst);
+    reset_capture_epoch();
+    set_recording(true);
+    let audio = AudioRecorder::start(config.audio_mode, &config.output_path);
+    let sink = RecordingSink {
+        session,
+        writer: Some(writer),
+        audio,
+        output_path: config.output_path.clone(),
+        parts_dir: config.parts_dir.clone(),
+        max_duration_secs: config.max_duration_secs,
+        warned: false,
+        last_printed_secs: 0,
+        last_pts: None,
+        stats: CaptureStats::new(config.fps),
+    };
+    if let Err(e) = source.start(Box::new(sink)) {
         set_recording(false);
+        return Err(e);
     }
-}
 
-#[cfg(windows)]
-pub use windows_impl::{start_recording, stop_recording};
+    let (x, y) = source.origin();
+    *ACTIVE_SOURCE.lock().unwrap() = Some(source);
+    Ok(CaptureArea {
+        x,
+        y,
+        width,
+        height,
+    })
+}
 
-// macOS stub
-#[cfg(target_os = "macos")]
-pub fn start_recording(
-    _config: RecorderConfig,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("macOS recording not yet implemented");
-    Ok(())
+/// Start recording `config.target` with this platform's capture source
+pub fn start_recording(config: RecorderConfig) -> Result<CaptureArea, CaptureError> {
+    let source = create_source(&config.target, config.fps)?;
+    record_with_source(source, config)
 }
 
-#[cfg(target_os = "macos")]
+/// Stop the current recording
+///
+/// The source is shut down on a background thread so encoder flushing never
+/// blocks the caller.
 pub fn stop_recording() {
-    println!("Stopping macOS recording");
+    set_recording(false);
+    if let Some(mut source) = ACTIVE_SOURCE.lock().unwrap().take() {
+        std::thread::spawn(move || source.stop());
+    }
+}
+
+/// Held by tests that drive the recorder's process-wide state, so the test
+/// runner's threads don't record over each other
+#[cfg(test)]
+pub(crate) static TEST_STATE_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::synthetic::SyntheticSource;
+    use std::sync::{MutexGuard, PoisonError};
+
+    /// Take [`TEST_STATE_LOCK`] and reset the recorder's state, including
+    /// whatever a failed test left behind
+    fn lock_recorder_state() -> MutexGuard<'static, ()> {
+        let guard = TEST_STATE_LOCK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        set_recording(false);
+        let previous = ACTIVE_SOURCE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(mut source) = previous {
+            source.stop();
+        }
+        *PAUSE_CLOCK.lock().unwrap_or_else(PoisonError::into_inner) = PauseClock {
+            paused_at: None,
+            total: Duration::ZERO,
+        };
+        DURATION_WARNING.store(false, Ordering::SeqCst);
+        reset_capture_epoch();
+        guard
+    }
+
+    #[test]
+    fn records_synthetic_frames() {
+        let _state = lock_recorder_state();
+        let dir = std::env::temp_dir().join(format!("demo-recorder-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let output_path = dir.join("synthetic.mp4");
+        let config = RecorderConfig {
+            output_path: output_path.clone(),
+            events_path: dir.join("synthetic.events"),
+            parts_dir: dir.join("synthetic.parts"),
+            width: 0,
+            height: 0,
+            fps: 10,
+            target: CaptureTarget::PrimaryMonitor,
+            // Frames are 100ms apart, so the cutoff ends the recording after 10
+            max_duration_secs: Some(1),
+            audio_mode: AudioMode::None,
+        };
+
+        let area = record_with_source(Box::new(SyntheticSource::new(64, 48, 10)), config)
+            .expect("recording should start");
+        assert_eq!((area.width, area.height), (64, 48));
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while is_recording() {
+            assert!(Instant::now() < deadline, "recording did not stop");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        // Joins the capture thread, which finishes the video before exiting
+        if let Some(mut source) = ACTIVE_SOURCE.lock().unwrap().take() {
+            source.stop();
+        }
+
+        let size = std::fs::metadata(&output_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        assert!(size > 0, "no video written to {output_path:?}");
+        assert!(!dir.join("synthetic.parts").exists());
+
+        let stats = CaptureStats::load(&stats_path(&output_path)).expect("stats saved");
+        assert_eq!(stats.target_fps, 10);
+        assert_eq!(stats.frames, 10);
+        assert_eq!(stats.dropped_frames, 0);
+        assert_eq!(stats.duplicate_frames, 0);
+        assert_eq!(
+            stats.frame_times_ms,
+            (0..10).map(|i| i * 100).collect::<Vec<u64>>()
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,90 @@
+//! Recovery of recordings interrupted by a crash
+//!
+//! A recording in progress lives in a `<name>.parts` folder next to its final
+//! file: video segments plus an event journal. A clean stop joins them and
+//! removes the folder, so any `.parts` folder found at launch belongs to a
+//! recording that never finished.
+
+use super::audio::{mux_audio, recover_tracks};
+use super::ffmpeg::{concat_segments, ffmpeg_available, is_complete};
+use super::source::CaptureError;
+use crate::zoom::{recover_event_journal, save_event_log, EVENT_JOURNAL_FILE, EVENT_LOG_EXTENSION};
+use std::path::{Path, PathBuf};
+
+/// Stitch every interrupted recording in `folder` into a normal recording
+///
+/// Returns the recovered video (or event log, if no video survived) paths.
+pub fn recover_sessions(folder: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut recovered = Vec::new();
+    for entry in read_dir.flatten() {
+        let dir = entry.path();
+        if !dir.is_dir() || dir.extension().is_none_or(|e| e != "parts") {
+            continue;
+        }
+        match recover_session(&dir) {
+            Ok(Some(path)) => recovered.push(path),
+            Ok(None) => {}
+            Err(e) => eprintln!("Could not recover {dir:?}: {e}"),
+        }
+    }
+    recovered
+}
+
+/// Recover one `.parts` folder, removing it once everything in it is saved
+fn recover_session(dir: &Path) -> Result<Option<PathBuf>, CaptureError> {
+    let folder = dir.parent().ok_or("Recording folder has no parent")?;
+    let stem = dir
+        .file_stem()
+        .ok_or("Recording folder has no name")?
+        .to_string_lossy();
+    let video_path = folder.join(format!("{stem}.mp4"));
+    let events_path = folder.join(format!("{stem}.{EVENT_LOG_EXTENSION}"));
+    let mut recovered = None;
+
+    // Events first: they are worth keeping even if no video survived
+    let journal = dir.join(EVENT_JOURNAL_FILE);
+    if journal.exists() && !events_path.exists() {
+        let log = recover_event_journal(&journal).map_err(|e| e.to_string())?;
+        save_event_log(&log, &events_path).map_err(|e| e.to_string())?;
+        println!("Recovered {} events to {:?}", log.events.len(), events_path);
+        recovered = Some(events_path);
+    }
+
+    let mut segments: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|e| e == "mp4")
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("segment_"))
+        })
+        .collect();
+    if !segments.is_empty() && !video_path.exists() {
+        if !ffmpeg_available() {
+            return Err("ffmpeg is needed to recover the video".into());
+        }
+        segments.sort();
+        // The segment being written when the app died can't be read
+        segments.retain(|segment| is_complete(segment));
+        if !segments.is_empty() {
+            concat_segments(&segments, &video_path)?;
+            let tracks = recover_tracks(&video_path);
+            if let Err(e) = mux_audio(&video_path, &tracks) {
+                eprintln!("Recovered audio kept as separate WAV files: {e}");
+            }
+            println!(
+                "Recovered {} segment(s) to {:?}",
+                segments.len(),
+                video_path
+            );
+            recovered = Some(video_path);
+        }
+    }
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(recovered)
+}
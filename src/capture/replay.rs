@@ -0,0 +1,234 @@
+//! Instant replay: keep the last few seconds of screen on disk while idle
+//!
+//! The replay buffer records into short segments (see [`SegmentWriter`]) and
+//! deletes the ones that fall out of the window. Saving joins the segments
+//! still on disk into a normal recording file. Saves don't wait for a frame:
+//! Windows Graphics Capture sends none while the screen doesn't change.
+
+use super::clock::{capture_epoch, mark_capture_epoch, reset_capture_epoch};
+use super::ffmpeg::concat_segments;
+use super::segments::SegmentWriter;
+use super::source::{
+    create_source, CaptureArea, CaptureError, CaptureSource, CapturedFrame, FrameSink,
+};
+use crate::config::Config;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Once};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Length of each buffered segment; the buffer holds up to one extra segment
+pub const REPLAY_SEGMENT_SECS: u64 = 2;
+
+/// A replay saved to disk and the part of the buffer timeline it covers
+#[derive(Debug, Clone)]
+pub struct SavedReplay {
+    pub video_path: PathBuf,
+    /// Buffer time of the first saved frame, matching event log timestamps
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Source feeding the replay buffer, kept so it can be stopped
+static ACTIVE_REPLAY: Mutex<Option<Box<dyn CaptureSource>>> = Mutex::new(None);
+
+/// Segments of the running replay buffer, for saving them
+static ACTIVE_BUFFER: Mutex<Option<Arc<Mutex<ReplayBuffer>>>> = Mutex::new(None);
+
+/// Incremented per replay buffer so a stopped sink ignores later requests
+static REPLAY_SESSION: AtomicU64 = AtomicU64::new(0);
+
+/// Clears segments left behind if the app was killed while buffering
+static STALE_CLEANUP: Once = Once::new();
+
+/// Outcome of the last save, waiting to be collected by the UI
+static SAVE_RESULT: Mutex<Option<Result<SavedReplay, String>>> = Mutex::new(None);
+
+/// Rolling window of segments, written by the capture thread and read by saves
+struct ReplayBuffer {
+    segments: Option<SegmentWriter>,
+    buffer: Duration,
+    /// Save in progress; segments aren't trimmed until it has read them
+    saving: Option<JoinHandle<()>>,
+}
+
+impl ReplayBuffer {
+    fn is_saving(&self) -> bool {
+        self.saving.as_ref().is_some_and(|h| !h.is_finished())
+    }
+
+    /// Finish the current segment and join the buffered ones into `output` in the background
+    fn save(&mut self, output: PathBuf) -> Result<(), CaptureError> {
+        let segments = self
+            .segments
+            .as_mut()
+            .ok_or("Instant replay stopped before saving")?;
+        segments.rotate()?;
+
+        // Buffer time now, which may be well past the last frame on a static screen
+        let now = capture_epoch()
+            .map(|epoch| epoch.elapsed())
+            .unwrap_or_default();
+        let window_start = now.saturating_sub(self.buffer);
+        let mut saved: Vec<_> = segments
+            .finished()
+            .filter(|s| s.end > window_start)
+            .cloned()
+            .collect();
+        if saved.is_empty() {
+            // Nothing changed on screen for the whole window; the last frames still show it
+            saved.extend(segments.finished().last().cloned());
+        }
+        let (Some(first), Some(last)) = (saved.first(), saved.last()) else {
+            return Err("Replay buffer is empty".into());
+        };
+        let start_ms = first.start.as_millis() as u64;
+        let end_ms = last.end.as_millis() as u64;
+        let paths: Vec<PathBuf> = saved.into_iter().map(|s| s.path).collect();
+
+        self.saving = Some(std::thread::spawn(move || {
+            let result = concat_segments(&paths, &output)
+                .map(|()| SavedReplay {
+                    video_path: output,
+                    start_ms,
+                    end_ms,
+                })
+                .map_err(|e| e.to_string());
+            *SAVE_RESULT.lock().unwrap() = Some(result);
+        }));
+        Ok(())
+    }
+}
+
+/// Frame sink that feeds a [`ReplayBuffer`]
+struct ReplaySink {
+    session: u64,
+    dir: PathBuf,
+    buffer: Arc<Mutex<ReplayBuffer>>,
+}
+
+impl FrameSink for ReplaySink {
+    fn on_frame(&mut self, frame: CapturedFrame<'_>) -> Result<bool, CaptureError> {
+        if REPLAY_SESSION.load(Ordering::SeqCst) != self.session {
+            return Ok(false);
+        }
+        mark_capture_epoch(frame.timestamp);
+
+        let mut buffer = self.buffer.lock().unwrap();
+        let saving = buffer.is_saving();
+        let window = buffer.buffer;
+        if let Some(ref mut segments) = buffer.segments {
+            segments.write(&frame, frame.timestamp)?;
+            if !saving {
+                segments.drop_before(frame.timestamp.saturating_sub(window));
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn on_stopped(&mut self) -> Result<(), CaptureError> {
+        let mut buffer = self.buffer.lock().unwrap();
+        // Let a save finish reading the segments before they are deleted
+        if let Some(handle) = buffer.saving.take() {
+            let _ = handle.join();
+        }
+        if let Some(segments) = buffer.segments.take() {
+            segments.finish()?;
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+        Ok(())
+    }
+}
+
+/// Start buffering the last `config.replay_buffer_secs` of `config.capture_target`
+///
+/// Returns the screen area being buffered.
+pub fn start_replay(config: &Config) -> Result<CaptureArea, CaptureError> {
+    stop_replay();
+
+    let mut source = create_source(&config.capture_target, config.fps.clamp(1, 240))?;
+    let (width, height) = source.resolution();
+
+    let session = REPLAY_SESSION.fetch_add(1, Ordering::SeqCst) + 1;
+    let root = config.output_folder.join(".replay");
+    STALE_CLEANUP.call_once(|| {
+        let _ = std::fs::remove_dir_all(&root);
+    });
+    let dir = root.join(format!("session_{session}"));
+    let segments = SegmentWriter::new(
+        &dir,
+        width,
+        height,
+        config.fps.clamp(1, 240),
+        Duration::from_secs(REPLAY_SEGMENT_SECS),
+    )?;
+
+    reset_capture_epoch();
+    let buffer = Arc::new(Mutex::new(ReplayBuffer {
+        segments: Some(segments),
+        buffer: Duration::from_secs(config.replay_buffer_secs as u64),
+        saving: None,
+    }));
+    let sink = ReplaySink {
+        session,
+        dir,
+        buffer: buffer.clone(),
+    };
+    source.start(Box::new(sink))?;
+    *ACTIVE_BUFFER.lock().unwrap() = Some(buffer);
+    println!(
+        "Instant replay buffering last {}s ({}x{})",
+        config.replay_buffer_secs, width, height
+    );
+
+    let (x, y) = source.origin();
+    *ACTIVE_REPLAY.lock().unwrap() = Some(source);
+    Ok(CaptureArea {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+/// Stop the replay buffer and delete its segments
+pub fn stop_replay() {
+    REPLAY_SESSION.fetch_add(1, Ordering::SeqCst);
+    ACTIVE_BUFFER.lock().unwrap().take();
+    if let Some(mut source) = ACTIVE_REPLAY.lock().unwrap().take() {
+        std::thread::spawn(move || source.stop());
+    }
+}
+
+/// Ask the replay buffer to save its contents to `output`
+///
+/// The result is reported through [`take_replay_saved`].
+pub fn request_replay_save(output: PathBuf) {
+    let Some(buffer) = ACTIVE_BUFFER.lock().unwrap().clone() else {
+        *SAVE_RESULT.lock().unwrap() = Some(Err("Instant replay is off".to_string()));
+        return;
+    };
+    // Finishing the open segment encodes its last frames, so it happens off
+    // the caller's thread
+    std::thread::spawn(move || {
+        // One save at a time; the next waits until the previous has read its segments
+        let result = loop {
+            let mut buffer = buffer.lock().unwrap();
+            if !buffer.is_saving() {
+                break buffer.save(output);
+            }
+            drop(buffer);
+            std::thread::sleep(Duration::from_millis(50));
+        };
+        if let Err(e) = result {
+            *SAVE_RESULT.lock().unwrap() = Some(Err(e.to_string()));
+        }
+    });
+}
+
+/// Collect the result of a finished replay save, if any
+pub fn take_replay_saved() -> Option<Result<SavedReplay, String>> {
+    SAVE_RESULT.lock().unwrap().take()
+}
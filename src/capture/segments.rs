@@ -0,0 +1,131 @@
+//! Recording into a sequence of short, independently playable MP4 segments
+//!
+//! Each segment is a complete file, so finished segments survive a crash and
+//! old ones can be dropped from the front (instant replay). Segments are
+//! joined with [`super::ffmpeg::concat_segments`].
+
+use super::source::{CaptureError, CapturedFrame};
+use super::writer::VideoWriter;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A finished segment and the part of the recording timeline it covers
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub path: PathBuf,
+    pub start: Duration,
+    pub end: Duration,
+}
+
+/// The segment currently being written
+struct OpenSegment {
+    writer: VideoWriter,
+    path: PathBuf,
+    start: Duration,
+    last_pts: Duration,
+}
+
+/// Writes frames into numbered segments of roughly `segment_len` each
+pub struct SegmentWriter {
+    dir: PathBuf,
+    width: u32,
+    height: u32,
+    fps: u32,
+    segment_len: Duration,
+    next_index: u32,
+    current: Option<OpenSegment>,
+    finished: VecDeque<Segment>,
+}
+
+impl SegmentWriter {
+    /// Write `width`x`height` segments into `dir`, which is created if needed
+    pub fn new(
+        dir: &Path,
+        width: u32,
+        height: u32,
+        fps: u32,
+        segment_len: Duration,
+    ) -> Result<Self, CaptureError> {
+        std::fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            width,
+            height,
+            fps,
+            segment_len,
+            next_index: 0,
+            current: None,
+            finished: VecDeque::new(),
+        })
+    }
+
+    /// Path of segment number `index` in `dir`
+    pub fn segment_path(dir: &Path, index: u32) -> PathBuf {
+        dir.join(format!("segment_{index:05}.mp4"))
+    }
+
+    /// Encode one frame at recording time `pts`, starting a new segment when due
+    pub fn write(&mut self, frame: &CapturedFrame<'_>, pts: Duration) -> Result<(), CaptureError> {
+        let due = self
+            .current
+            .as_ref()
+            .is_some_and(|open| pts.saturating_sub(open.start) >= self.segment_len);
+        if due {
+            self.rotate()?;
+        }
+
+        if self.current.is_none() {
+            let path = Self::segment_path(&self.dir, self.next_index);
+            self.next_index += 1;
+            let writer = VideoWriter::new(&path, self.width, self.height, self.fps)?;
+            self.current = Some(OpenSegment {
+                writer,
+                path,
+                start: pts,
+                last_pts: pts,
+            });
+        }
+
+        let open = self.current.as_mut().expect("segment was just opened");
+        // Each segment's own timeline starts at zero
+        open.writer.write(frame, pts.saturating_sub(open.start))?;
+        open.last_pts = pts;
+        Ok(())
+    }
+
+    /// Finish the open segment (if any) so everything written so far is playable
+    pub fn rotate(&mut self) -> Result<(), CaptureError> {
+        if let Some(open) = self.current.take() {
+            open.writer.finish()?;
+            // The last frame is shown for one frame interval
+            let frame_len = Duration::from_secs_f64(1.0 / self.fps.max(1) as f64);
+            self.finished.push_back(Segment {
+                path: open.path,
+                start: open.start,
+                end: open.last_pts + frame_len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Finished segments, oldest first
+    pub fn finished(&self) -> impl Iterator<Item = &Segment> {
+        self.finished.iter()
+    }
+
+    /// Delete finished segments that end at or before `time`
+    pub fn drop_before(&mut self, time: Duration) {
+        while self.finished.front().is_some_and(|s| s.end <= time) {
+            if let Some(segment) = self.finished.pop_front() {
+                let _ = std::fs::remove_file(&segment.path);
+            }
+        }
+    }
+
+    /// Finish the open segment and return every segment still on disk
+    pub fn finish(mut self) -> Result<Vec<Segment>, CaptureError> {
+        self.rotate()?;
+        Ok(self.finished.into_iter().collect())
+    }
+}
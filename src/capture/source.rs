@@ -0,0 +1,255 @@
+//! Capture sources that feed frames into the recorder pipeline
+//!
+//! Each platform backend implements [`CaptureSource`]; the recorder hands it
+//! a [`FrameSink`] and never touches platform capture APIs directly.
+
+use crate::config::CaptureTarget;
+use std::time::Duration;
+
+/// Error type shared by capture sources, sinks and writers
+pub type CaptureError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Pixel layout of frames delivered by a source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba8,
+    Bgra8,
+    Rgb8,
+}
+
+impl PixelFormat {
+    /// Bytes used by a single pixel
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            PixelFormat::Rgba8 | PixelFormat::Bgra8 => 4,
+            PixelFormat::Rgb8 => 3,
+        }
+    }
+}
+
+/// Screen-space rectangle covered by a capture source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CaptureArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CaptureArea {
+    /// Overlap of two areas, `None` if they don't intersect
+    pub fn intersect(&self, other: &CaptureArea) -> Option<CaptureArea> {
+        let x0 = self.x.max(other.x);
+        let y0 = self.y.max(other.y);
+        let x1 = (self.x + self.width as i32).min(other.x + other.width as i32);
+        let y1 = (self.y + self.height as i32).min(other.y + other.height as i32);
+        (x1 > x0 && y1 > y0).then(|| CaptureArea {
+            x: x0,
+            y: y0,
+            width: (x1 - x0) as u32,
+            height: (y1 - y0) as u32,
+        })
+    }
+}
+
+/// A display that can be chosen as a capture target
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: String,
+    /// Bounds in virtual-screen coordinates
+    pub area: CaptureArea,
+    pub primary: bool,
+    /// Display scale, 1.0 at 96 DPI
+    pub scale_factor: f64,
+}
+
+/// A top-level window that can be chosen as a capture target
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    pub title: String,
+    pub process: String,
+}
+
+impl WindowInfo {
+    /// Whether this window satisfies a title substring and/or process name filter
+    pub fn matches(&self, title: Option<&str>, process: Option<&str>) -> bool {
+        let title_ok = title.is_none_or(|t| self.title.to_lowercase().contains(&t.to_lowercase()));
+        let process_ok = process.is_none_or(|p| self.process.eq_ignore_ascii_case(p));
+        title_ok && process_ok
+    }
+}
+
+/// Pick the best match for a window selection from `windows`
+///
+/// Prefers a window matching both title and process, then falls back to the
+/// process alone (titles change as documents/tabs switch), then the title alone.
+pub fn select_window(
+    windows: &[WindowInfo],
+    title: Option<&str>,
+    process: Option<&str>,
+) -> Option<usize> {
+    if title.is_none() && process.is_none() {
+        return None;
+    }
+    windows
+        .iter()
+        .position(|w| w.matches(title, process))
+        .or_else(|| process.and_then(|_| windows.iter().position(|w| w.matches(None, process))))
+        .or_else(|| title.and_then(|_| windows.iter().position(|w| w.matches(title, None))))
+}
+
+/// A single captured frame, borrowed from the source for the duration of the callback
+pub struct CapturedFrame<'a> {
+    /// Tightly packed pixel rows (no stride padding)
+    pub data: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    /// Time since the source delivered its first frame
+    pub timestamp: Duration,
+}
+
+/// Receives frames from a running [`CaptureSource`]
+pub trait FrameSink: Send {
+    /// Handle one frame. Return `Ok(false)` to ask the source to stop.
+    fn on_frame(&mut self, frame: CapturedFrame<'_>) -> Result<bool, CaptureError>;
+
+    /// Called exactly once after the last frame, however the capture ended
+    fn on_stopped(&mut self) -> Result<(), CaptureError>;
+}
+
+/// A platform (or synthetic) producer of screen frames
+pub trait CaptureSource: Send {
+    /// Size of the frames this source produces
+    fn resolution(&self) -> (u32, u32);
+
+    /// Screen position of the captured area's top-left corner
+    fn origin(&self) -> (i32, i32) {
+        (0, 0)
+    }
+
+    /// Pixel layout of the frames this source produces
+    fn pixel_format(&self) -> PixelFormat;
+
+    /// Start delivering frames to `sink` from a background thread
+    fn start(&mut self, sink: Box<dyn FrameSink>) -> Result<(), CaptureError>;
+
+    /// Stop capturing and wait until the sink has been told the capture ended
+    fn stop(&mut self);
+}
+
+/// Create the capture source for `target` on this platform
+///
+/// Setting `DEMO_RECORDER_SOURCE=synthetic` swaps in generated frames so the
+/// recorder can run in CI without a display.
+pub fn create_source(
+    target: &CaptureTarget,
+    fps: u32,
+) -> Result<Box<dyn CaptureSource>, CaptureError> {
+    if std::env::var("DEMO_RECORDER_SOURCE").as_deref() == Ok("synthetic") {
+        return Ok(Box::new(super::synthetic::SyntheticSource::new(
+            1920, 1080, fps,
+        )));
+    }
+
+    #[cfg(windows)]
+    {
+        Ok(Box::new(super::windows::WindowsSource::for_target(target)?))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(super::x11::X11Source::for_target(target, fps)?))
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let _ = (target, fps);
+        Err("Screen capture is not implemented on this platform".into())
+    }
+}
+
+/// List monitors in the order used by [`CaptureTarget::Monitor`]
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    #[cfg(windows)]
+    {
+        super::windows::list_monitors()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        super::x11::list_monitors()
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        Vec::new()
+    }
+}
+
+/// Watches which top-level window is in front
+///
+/// On X11 it keeps its display connection, so polling it is cheap.
+pub struct ForegroundWatcher {
+    #[cfg(target_os = "linux")]
+    grabber: Option<super::x11::X11Grabber>,
+}
+
+impl ForegroundWatcher {
+    pub fn connect() -> Self {
+        Self {
+            #[cfg(target_os = "linux")]
+            grabber: super::x11::X11Grabber::connect().ok(),
+        }
+    }
+
+    /// Description and screen bounds of the window in front, if any
+    pub fn current(&mut self) -> Option<(WindowInfo, CaptureArea)> {
+        #[cfg(windows)]
+        {
+            super::windows::foreground_window()
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.grabber.as_ref()?.foreground_window()
+        }
+
+        #[cfg(not(any(windows, target_os = "linux")))]
+        {
+            None
+        }
+    }
+}
+
+/// Display scale of the monitor holding the top-left corner of `area`, 1.0 if unknown
+pub fn scale_factor_at(area: CaptureArea) -> f64 {
+    let corner = CaptureArea {
+        x: area.x,
+        y: area.y,
+        width: 1,
+        height: 1,
+    };
+    list_monitors()
+        .into_iter()
+        .find(|monitor| monitor.area.intersect(&corner).is_some())
+        .map_or(1.0, |monitor| monitor.scale_factor)
+}
+
+/// List top-level windows that can be selected as capture targets
+pub fn list_windows() -> Vec<WindowInfo> {
+    #[cfg(windows)]
+    {
+        super::windows::list_windows()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        super::x11::list_windows()
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        Vec::new()
+    }
+}
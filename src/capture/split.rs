@@ -0,0 +1,42 @@
+//! Splitting a recording into parts at its chapter markers
+//!
+//! Each part gets its own video and event log, named after the recording
+//! with a `_partN` suffix, so it shows up in the library like any other
+//! recording.
+
+use super::ffmpeg::{cut_video, ffmpeg_available, probe_duration_ms};
+use super::source::CaptureError;
+use crate::zoom::{event_log_path, find_event_log, load_event_log, save_event_log};
+use std::path::{Path, PathBuf};
+
+/// Cut `video` at every marker in its event log, returning the parts in order
+pub fn split_at_markers(video: &Path) -> Result<Vec<PathBuf>, CaptureError> {
+    if !ffmpeg_available() {
+        return Err("ffmpeg is needed to split recordings".into());
+    }
+    let events_path = find_event_log(video).ok_or("Recording has no event log")?;
+    let log = load_event_log(&events_path).map_err(|e| e.to_string())?;
+    let duration_ms = probe_duration_ms(video).ok_or("Could not read the recording's length")?;
+    let chapters = log.chapters(duration_ms);
+    if chapters.len() < 2 {
+        return Err("Recording has no markers to split at".into());
+    }
+
+    let stem = video
+        .file_stem()
+        .ok_or("Recording has no name")?
+        .to_string_lossy();
+    let mut parts = Vec::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let part = video.with_file_name(format!("{stem}_part{}.mp4", i + 1));
+        cut_video(video, chapter.start_ms, chapter.end_ms, &part)?;
+        let part_log = log.slice(chapter.start_ms, chapter.end_ms);
+        save_event_log(&part_log, &event_log_path(&part)).map_err(|e| e.to_string())?;
+        println!(
+            "Split \"{}\" ({} ms - {} ms) to {:?}",
+            chapter.title, chapter.start_ms, chapter.end_ms, part
+        );
+        parts.push(part);
+    }
+    Ok(parts)
+}
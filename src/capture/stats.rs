@@ -0,0 +1,95 @@
+//! Frame timing statistics for a recording
+//!
+//! The recorder notes when every encoded frame landed on the video timeline
+//! and compares the spacing against the target frame rate. The result is
+//! saved as a `.capture.json` sidecar next to the video; the dashboard warns
+//! about recordings with many drops and the zoom pipeline reads the real frame
+//! times from it.
+
+use super::source::CaptureError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Share of dropped frames above which a recording is flagged
+const SIGNIFICANT_DROP_PERCENT: f32 = 5.0;
+
+/// Frame timing of one recording
+///
+/// Sources that only deliver frames when the screen changes (Windows Graphics
+/// Capture) leave gaps on a static screen, which are counted as drops too.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CaptureStats {
+    pub target_fps: u32,
+    /// Frames encoded into the video
+    pub frames: u64,
+    /// Frame slots at the target rate that got no frame
+    pub dropped_frames: u64,
+    /// Frames that arrived less than half a frame interval after the previous one
+    /// (or out of order, in which case they weren't encoded)
+    pub duplicate_frames: u64,
+    /// Video time of every encoded frame, in milliseconds
+    pub frame_times_ms: Vec<u64>,
+}
+
+impl CaptureStats {
+    pub fn new(target_fps: u32) -> Self {
+        Self {
+            target_fps: target_fps.max(1),
+            ..Default::default()
+        }
+    }
+
+    fn frame_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.target_fps.max(1) as f64)
+    }
+
+    /// Note a frame encoded at video time `pts`
+    pub fn record_frame(&mut self, pts: Duration) {
+        let interval = self.frame_interval().as_secs_f64();
+        if let Some(&last_ms) = self.frame_times_ms.last() {
+            let gap = pts.as_secs_f64() - last_ms as f64 / 1000.0;
+            let slots = (gap / interval).round() as u64;
+            if slots > 1 {
+                self.dropped_frames += slots - 1;
+            } else if gap < interval / 2.0 {
+                self.duplicate_frames += 1;
+            }
+        }
+        self.frames += 1;
+        self.frame_times_ms.push(pts.as_millis() as u64);
+    }
+
+    /// Note a frame that was discarded because it didn't advance the timeline
+    pub fn record_duplicate(&mut self) {
+        self.duplicate_frames += 1;
+    }
+
+    /// Dropped frames as a percentage of the frames the recording should have had
+    pub fn drop_percent(&self) -> f32 {
+        let expected = self.frames + self.dropped_frames;
+        if expected == 0 {
+            return 0.0;
+        }
+        self.dropped_frames as f32 * 100.0 / expected as f32
+    }
+
+    /// Whether enough frames were dropped to be worth a warning
+    pub fn has_significant_drops(&self) -> bool {
+        self.drop_percent() >= SIGNIFICANT_DROP_PERCENT
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CaptureError> {
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CaptureError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// Path of the stats sidecar for `video`
+pub fn stats_path(video: &Path) -> PathBuf {
+    video.with_extension("capture.json")
+}
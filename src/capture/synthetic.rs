@@ -0,0 +1,121 @@
+//! Synthetic capture source that generates frames instead of reading a display
+//!
+//! Draws a scrolling gradient with a moving box so encoded output visibly
+//! changes over time. Used to exercise the recorder pipeline in CI.
+
+use super::source::{CaptureError, CaptureSource, CapturedFrame, FrameSink, PixelFormat};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Generates RGBA frames at a fixed rate
+pub struct SyntheticSource {
+    width: u32,
+    height: u32,
+    fps: u32,
+    stop_flag: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SyntheticSource {
+    /// Create a source producing `width`x`height` frames paced in real time at `fps`
+    pub fn new(width: u32, height: u32, fps: u32) -> Self {
+        Self {
+            width,
+            height,
+            fps: fps.max(1),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            thread: None,
+        }
+    }
+}
+
+/// Fill `buffer` with the test pattern for frame `index`
+fn draw_frame(buffer: &mut [u8], width: u32, height: u32, index: u64) {
+    let box_size = (height / 6).max(1);
+    let box_x = (index * 8) % width.saturating_sub(box_size).max(1) as u64;
+    let box_y = (height - box_size) / 2;
+
+    for y in 0..height {
+        for x in 0..width {
+            let idx = ((y * width + x) * 4) as usize;
+            let in_box = (x as u64) >= box_x
+                && (x as u64) < box_x + box_size as u64
+                && y >= box_y
+                && y < box_y + box_size;
+
+            if in_box {
+                buffer[idx..idx + 4].copy_from_slice(&[255, 255, 255, 255]);
+            } else {
+                buffer[idx] = ((x as u64 + index) % 256) as u8;
+                buffer[idx + 1] = (y % 256) as u8;
+                buffer[idx + 2] = ((index * 2) % 256) as u8;
+                buffer[idx + 3] = 255;
+            }
+        }
+    }
+}
+
+impl CaptureSource for SyntheticSource {
+    fn resolution(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn pixel_format(&self) -> PixelFormat {
+        PixelFormat::Rgba8
+    }
+
+    fn start(&mut self, mut sink: Box<dyn FrameSink>) -> Result<(), CaptureError> {
+        let (width, height, fps) = (self.width, self.height, self.fps);
+        let stop_flag = self.stop_flag.clone();
+        stop_flag.store(false, Ordering::SeqCst);
+
+        self.thread = Some(std::thread::spawn(move || {
+            let frame_interval = Duration::from_secs_f64(1.0 / fps as f64);
+            let start = Instant::now();
+            let mut buffer = vec![0u8; (width * height * 4) as usize];
+            let mut index = 0u64;
+
+            while !stop_flag.load(Ordering::SeqCst) {
+                draw_frame(&mut buffer, width, height, index);
+
+                let frame = CapturedFrame {
+                    data: &buffer,
+                    width,
+                    height,
+                    format: PixelFormat::Rgba8,
+                    timestamp: frame_interval * index as u32,
+                };
+                match sink.on_frame(frame) {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    Err(e) => {
+                        eprintln!("Synthetic capture error: {e}");
+                        break;
+                    }
+                }
+
+                index += 1;
+                let deadline = start + frame_interval * index as u32;
+                let now = Instant::now();
+                if deadline > now {
+                    std::thread::sleep(deadline - now);
+                }
+            }
+
+            if let Err(e) = sink.on_stopped() {
+                eprintln!("Synthetic capture error: {e}");
+            }
+        }));
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
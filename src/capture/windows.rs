@@ -0,0 +1,412 @@
+//! Windows capture source using the Windows Graphics Capture API
+//!
+//! Graphics Capture records a whole monitor or window; region targets capture
+//! the monitor holding the region and crop each frame.
+
+use super::source::{
+    select_window, CaptureArea, CaptureError, CaptureSource, CapturedFrame, FrameSink, MonitorInfo,
+    PixelFormat, WindowInfo,
+};
+use crate::config::CaptureTarget;
+use std::time::Duration;
+use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, HMONITOR, MONITORINFO};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use windows_capture::{
+    capture::{CaptureControl, Context, GraphicsCaptureApiHandler},
+    frame::Frame,
+    graphics_capture_api::InternalCaptureControl,
+    monitor::Monitor,
+    settings::{
+        ColorFormat, CursorCaptureSettings, DirtyRegionSettings, DrawBorderSettings,
+        MinimumUpdateIntervalSettings, SecondaryWindowSettings, Settings,
+        TryIntoCaptureItemWithType,
+    },
+    window::Window,
+};
+
+/// Settings passed to each new [`CaptureHandler`]
+pub struct HandlerFlags {
+    sink: Box<dyn FrameSink>,
+    /// Part of each frame to keep, in frame pixels; `None` keeps the whole frame
+    crop: Option<CaptureArea>,
+}
+
+/// Capture handler that forwards frames to a [`FrameSink`]
+pub struct CaptureHandler {
+    sink: Box<dyn FrameSink>,
+    crop: Option<CaptureArea>,
+    first_timestamp: Option<i64>,
+    stopped: bool,
+}
+
+impl CaptureHandler {
+    /// Tell the sink the capture ended (only the first call has an effect)
+    fn finish(&mut self) -> Result<(), CaptureError> {
+        if self.stopped {
+            return Ok(());
+        }
+        self.stopped = true;
+        self.sink.on_stopped()
+    }
+}
+
+impl GraphicsCaptureApiHandler for CaptureHandler {
+    type Flags = HandlerFlags;
+    type Error = CaptureError;
+
+    fn new(ctx: Context<Self::Flags>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sink: ctx.flags.sink,
+            crop: ctx.flags.crop,
+            first_timestamp: None,
+            stopped: false,
+        })
+    }
+
+    fn on_frame_arrived(
+        &mut self,
+        frame: &mut Frame,
+        capture_control: InternalCaptureControl,
+    ) -> Result<(), Self::Error> {
+        if self.stopped {
+            return Ok(());
+        }
+
+        // Frame timestamps are in 100ns units on an arbitrary system clock
+        let raw_timestamp = frame.timestamp().Duration;
+        let first = *self.first_timestamp.get_or_insert(raw_timestamp);
+        let timestamp = Duration::from_nanos((raw_timestamp - first).max(0) as u64 * 100);
+
+        let mut buffer = match self.crop {
+            Some(crop) => {
+                // Clamp in case the monitor mode changed since recording started
+                let end_x = (crop.x as u32 + crop.width).min(frame.width());
+                let end_y = (crop.y as u32 + crop.height).min(frame.height());
+                frame.buffer_crop(crop.x as u32, crop.y as u32, end_x, end_y)?
+            }
+            None => frame.buffer()?,
+        };
+        let width = buffer.width();
+        let height = buffer.height();
+        let data = buffer.as_nopadding_buffer()?;
+
+        let keep_going = self.sink.on_frame(CapturedFrame {
+            data,
+            width,
+            height,
+            format: PixelFormat::Bgra8,
+            timestamp,
+        })?;
+
+        if !keep_going {
+            self.finish()?;
+            capture_control.stop();
+        }
+
+        Ok(())
+    }
+
+    fn on_closed(&mut self) -> Result<(), Self::Error> {
+        println!("\nCapture session ended");
+        self.finish()
+    }
+}
+
+/// Capture settings shared by monitor and window capture
+fn capture_settings<T: TryIntoCaptureItemWithType>(
+    item: T,
+    flags: HandlerFlags,
+) -> Settings<HandlerFlags, T> {
+    Settings::new(
+        item,
+        CursorCaptureSettings::Default,
+        DrawBorderSettings::WithoutBorder,
+        SecondaryWindowSettings::Default,
+        MinimumUpdateIntervalSettings::Default,
+        DirtyRegionSettings::Default,
+        ColorFormat::Bgra8,
+        flags,
+    )
+}
+
+/// The item a [`WindowsSource`] captures
+#[derive(Clone, Copy)]
+enum CaptureItem {
+    Monitor(Monitor),
+    Window(Window),
+}
+
+/// Bounds of `monitor` in virtual-screen coordinates
+fn monitor_area(monitor: &Monitor) -> Result<CaptureArea, CaptureError> {
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    unsafe { GetMonitorInfoW(HMONITOR(monitor.as_raw_hmonitor()), &mut info) }.ok()?;
+    let rect = info.rcMonitor;
+    Ok(CaptureArea {
+        x: rect.left,
+        y: rect.top,
+        width: (rect.right - rect.left) as u32,
+        height: (rect.bottom - rect.top) as u32,
+    })
+}
+
+/// Display scale of `monitor`, 1.0 at 96 DPI
+fn monitor_scale(monitor: &Monitor) -> f64 {
+    let (mut dpi_x, mut dpi_y) = (0, 0);
+    let result = unsafe {
+        GetDpiForMonitor(
+            HMONITOR(monitor.as_raw_hmonitor()),
+            MDT_EFFECTIVE_DPI,
+            &mut dpi_x,
+            &mut dpi_y,
+        )
+    };
+    match result {
+        Ok(()) if dpi_x > 0 => dpi_x as f64 / 96.0,
+        _ => 1.0,
+    }
+}
+
+/// Monitors paired with their descriptions, in enumeration order
+fn monitors() -> Result<Vec<(Monitor, MonitorInfo)>, CaptureError> {
+    let primary = Monitor::primary()?.as_raw_hmonitor();
+    Monitor::enumerate()?
+        .into_iter()
+        .enumerate()
+        .map(|(i, monitor)| {
+            let info = MonitorInfo {
+                name: monitor
+                    .name()
+                    .unwrap_or_else(|_| format!("Monitor {}", i + 1)),
+                area: monitor_area(&monitor)?,
+                primary: monitor.as_raw_hmonitor() == primary,
+                scale_factor: monitor_scale(&monitor),
+            };
+            Ok((monitor, info))
+        })
+        .collect()
+}
+
+/// List connected monitors
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    monitors()
+        .map(|monitors| monitors.into_iter().map(|(_, info)| info).collect())
+        .unwrap_or_default()
+}
+
+/// Describe a window for matching, skipping ones without a title
+fn window_info(window: &Window) -> Option<WindowInfo> {
+    let title = window.title().ok().filter(|t| !t.trim().is_empty())?;
+    let process = window.process_name().unwrap_or_default();
+    Some(WindowInfo { title, process })
+}
+
+/// List capturable top-level windows
+pub fn list_windows() -> Vec<WindowInfo> {
+    Window::enumerate()
+        .map(|windows| windows.iter().filter_map(window_info).collect())
+        .unwrap_or_default()
+}
+
+/// Description and screen bounds of the window in front
+pub fn foreground_window() -> Option<(WindowInfo, CaptureArea)> {
+    let window = Window::foreground().ok()?;
+    let info = window_info(&window)?;
+    let rect = window.rect().ok()?;
+    let bounds = CaptureArea {
+        x: rect.left,
+        y: rect.top,
+        width: (rect.right - rect.left).max(0) as u32,
+        height: (rect.bottom - rect.top).max(0) as u32,
+    };
+    Some((info, bounds))
+}
+
+/// Captures a monitor or window through Windows Graphics Capture
+pub struct WindowsSource {
+    item: CaptureItem,
+    origin: (i32, i32),
+    width: u32,
+    height: u32,
+    crop: Option<CaptureArea>,
+    control: Option<CaptureControl<CaptureHandler, CaptureError>>,
+}
+
+impl WindowsSource {
+    /// Source for a whole monitor
+    pub fn monitor(monitor: Monitor) -> Result<Self, CaptureError> {
+        let area = monitor_area(&monitor)?;
+        let width = monitor.width()?;
+        let height = monitor.height()?;
+
+        println!(
+            "Monitor resolution: {}x{} at ({}, {})",
+            width, height, area.x, area.y
+        );
+
+        Ok(Self {
+            item: CaptureItem::Monitor(monitor),
+            origin: (area.x, area.y),
+            width,
+            height,
+            crop: None,
+            control: None,
+        })
+    }
+
+    /// Source for a screen region, captured from the monitor holding its top-left corner
+    ///
+    /// Parts of the region on other monitors are cut off.
+    pub fn region(region: CaptureArea) -> Result<Self, CaptureError> {
+        let (monitor, area) = monitors()?
+            .into_iter()
+            .find(|(_, info)| {
+                let a = info.area;
+                (a.x..a.x + a.width as i32).contains(&region.x)
+                    && (a.y..a.y + a.height as i32).contains(&region.y)
+            })
+            .map(|(monitor, info)| (monitor, info.area))
+            .ok_or("The capture region is not on any monitor")?;
+        let visible = area
+            .intersect(&region)
+            .ok_or("The capture region is not on any monitor")?;
+
+        // The encoder needs even dimensions
+        let width = visible.width.max(2) & !1;
+        let height = visible.height.max(2) & !1;
+
+        println!(
+            "Region: {}x{} at ({}, {})",
+            width, height, visible.x, visible.y
+        );
+
+        Ok(Self {
+            item: CaptureItem::Monitor(monitor),
+            origin: (visible.x, visible.y),
+            width,
+            height,
+            crop: Some(CaptureArea {
+                x: visible.x - area.x,
+                y: visible.y - area.y,
+                width,
+                height,
+            }),
+            control: None,
+        })
+    }
+
+    /// Source for a single window, sized to its bounds when recording starts
+    pub fn window(window: Window) -> Result<Self, CaptureError> {
+        let rect = window.rect()?;
+
+        // The encoder needs even dimensions
+        let width = ((rect.right - rect.left).max(2) as u32) & !1;
+        let height = ((rect.bottom - rect.top).max(2) as u32) & !1;
+
+        println!(
+            "Window \"{}\": {}x{} at ({}, {})",
+            window.title().unwrap_or_default(),
+            width,
+            height,
+            rect.left,
+            rect.top
+        );
+
+        Ok(Self {
+            item: CaptureItem::Window(window),
+            origin: (rect.left, rect.top),
+            width,
+            height,
+            crop: None,
+            control: None,
+        })
+    }
+
+    /// Source for a configured capture target
+    pub fn for_target(target: &CaptureTarget) -> Result<Self, CaptureError> {
+        match target {
+            CaptureTarget::PrimaryMonitor => Self::monitor(Monitor::primary()?),
+            CaptureTarget::Monitor(index) => {
+                let monitor = Monitor::enumerate()?
+                    .get(*index)
+                    .copied()
+                    .ok_or_else(|| format!("Monitor {} not found", index + 1))?;
+                Self::monitor(monitor)
+            }
+            CaptureTarget::Region { x, y, w, h } => Self::region(CaptureArea {
+                x: *x,
+                y: *y,
+                width: *w,
+                height: *h,
+            }),
+            CaptureTarget::ForegroundWindow => {
+                let window = Window::foreground()?;
+                // Clicking "Record" leaves our own window in front; only the hotkey makes sense here
+                if window.process_id()? == std::process::id() {
+                    return Err(
+                        "DemoRecorder is the foreground window; use the hotkey from the window you want to record"
+                            .into(),
+                    );
+                }
+                Self::window(window)
+            }
+            CaptureTarget::Window { title, process } => {
+                let windows: Vec<(Window, WindowInfo)> = Window::enumerate()?
+                    .into_iter()
+                    .filter_map(|w| window_info(&w).map(|info| (w, info)))
+                    .collect();
+                let infos: Vec<WindowInfo> = windows.iter().map(|(_, info)| info.clone()).collect();
+                let index = select_window(&infos, title.as_deref(), process.as_deref())
+                    .ok_or("No open window matches the selected capture window")?;
+                Self::window(windows[index].0)
+            }
+        }
+    }
+}
+
+impl CaptureSource for WindowsSource {
+    fn resolution(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn origin(&self) -> (i32, i32) {
+        self.origin
+    }
+
+    fn pixel_format(&self) -> PixelFormat {
+        PixelFormat::Bgra8
+    }
+
+    fn start(&mut self, sink: Box<dyn FrameSink>) -> Result<(), CaptureError> {
+        let flags = HandlerFlags {
+            sink,
+            crop: self.crop,
+        };
+        let control = match self.item {
+            CaptureItem::Monitor(monitor) => {
+                CaptureHandler::start_free_threaded(capture_settings(monitor, flags))
+            }
+            CaptureItem::Window(window) => {
+                CaptureHandler::start_free_threaded(capture_settings(window, flags))
+            }
+        }
+        .map_err(|e| -> CaptureError { e.to_string().into() })?;
+        self.control = Some(control);
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        if let Some(control) = self.control.take() {
+            let handler = control.callback();
+            if let Err(e) = control.stop() {
+                eprintln!("Capture error: {e}");
+            }
+            if let Err(e) = handler.lock().finish() {
+                eprintln!("Capture error: {e}");
+            }
+        }
+    }
+}
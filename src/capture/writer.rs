@@ -0,0 +1,163 @@
+//! Video file writer for captured frames
+//!
+//! On Windows frames go to the Media Foundation H.264 encoder from
+//! windows-capture; elsewhere they are encoded with video-rs.
+
+use super::source::{CaptureError, CapturedFrame, PixelFormat};
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(windows)]
+use windows_capture::encoder::{
+    AudioSettingsBuilder, ContainerSettingsBuilder, VideoEncoder, VideoSettingsBuilder,
+    VideoSettingsSubType,
+};
+
+/// Convert a frame into `width`x`height` packed pixels of the `target` format in `out`
+///
+/// Frames of a different size (e.g. a resized window) are cropped or padded
+/// with black from the top-left corner.
+fn convert_pixels(
+    frame: &CapturedFrame<'_>,
+    target: PixelFormat,
+    width: u32,
+    height: u32,
+    out: &mut Vec<u8>,
+) {
+    let src_bpp = frame.format.bytes_per_pixel();
+    let dst_bpp = target.bytes_per_pixel();
+    let same_size = (frame.width, frame.height) == (width, height);
+    out.resize((width * height) as usize * dst_bpp, 0);
+
+    if same_size && frame.format == target {
+        out.copy_from_slice(frame.data);
+        return;
+    }
+    if !same_size {
+        out.fill(0);
+    }
+
+    let copy_width = frame.width.min(width) as usize;
+    let copy_height = frame.height.min(height) as usize;
+    for row in 0..copy_height {
+        let src_row = &frame.data[row * frame.width as usize * src_bpp..][..copy_width * src_bpp];
+        let dst_row = &mut out[row * width as usize * dst_bpp..][..copy_width * dst_bpp];
+
+        if frame.format == target {
+            dst_row.copy_from_slice(src_row);
+            continue;
+        }
+
+        for (src, dst) in src_row
+            .chunks_exact(src_bpp)
+            .zip(dst_row.chunks_exact_mut(dst_bpp))
+        {
+            let (r, g, b) = match frame.format {
+                PixelFormat::Bgra8 => (src[2], src[1], src[0]),
+                PixelFormat::Rgba8 | PixelFormat::Rgb8 => (src[0], src[1], src[2]),
+            };
+            match target {
+                PixelFormat::Bgra8 => dst[..3].copy_from_slice(&[b, g, r]),
+                PixelFormat::Rgba8 | PixelFormat::Rgb8 => dst[..3].copy_from_slice(&[r, g, b]),
+            }
+        }
+    }
+}
+
+/// Encodes frames of a fixed size into an MP4 file
+pub struct VideoWriter {
+    #[cfg(windows)]
+    encoder: VideoEncoder,
+    #[cfg(not(windows))]
+    encoder: video_rs::encode::Encoder,
+    width: u32,
+    height: u32,
+    scratch: Vec<u8>,
+}
+
+impl VideoWriter {
+    /// Create a writer for `width`x`height` video at `fps`
+    pub fn new(path: &Path, width: u32, height: u32, fps: u32) -> Result<Self, CaptureError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        #[cfg(windows)]
+        let encoder = VideoEncoder::new(
+            VideoSettingsBuilder::new(width, height)
+                .sub_type(VideoSettingsSubType::H264) // H.264 for WMP compatibility
+                .frame_rate(fps),
+            // Audio is recorded separately and muxed in afterwards (see `audio`)
+            AudioSettingsBuilder::default().disabled(true),
+            ContainerSettingsBuilder::default(),
+            path,
+        )?;
+
+        #[cfg(not(windows))]
+        let encoder = {
+            let _ = fps; // video-rs derives the rate from frame timestamps
+            video_rs::init()?;
+            let settings = video_rs::encode::Settings::preset_h264_yuv420p(
+                width as usize,
+                height as usize,
+                false,
+            );
+            video_rs::encode::Encoder::new(path, settings)?
+        };
+
+        Ok(Self {
+            encoder,
+            width,
+            height,
+            scratch: Vec::new(),
+        })
+    }
+
+    /// Encode one frame at presentation time `pts`
+    pub fn write(&mut self, frame: &CapturedFrame<'_>, pts: Duration) -> Result<(), CaptureError> {
+        let (width, height) = (self.width, self.height);
+
+        #[cfg(windows)]
+        {
+            let passthrough = frame.format == PixelFormat::Bgra8
+                && (frame.width, frame.height) == (width, height);
+            let data = if passthrough {
+                frame.data
+            } else {
+                convert_pixels(frame, PixelFormat::Bgra8, width, height, &mut self.scratch);
+                &self.scratch
+            };
+            // Media Foundation timestamps are in 100ns units
+            self.encoder
+                .send_frame_buffer(data, (pts.as_nanos() / 100) as i64)?;
+        }
+
+        #[cfg(not(windows))]
+        {
+            let mut data = std::mem::take(&mut self.scratch);
+            convert_pixels(frame, PixelFormat::Rgb8, width, height, &mut data);
+            let shape = (height as usize, width as usize, 3);
+            let rgb = video_rs::Frame::from_shape_vec(shape, data)?;
+            self.encoder
+                .encode(&rgb, video_rs::Time::from_secs_f64(pts.as_secs_f64()))?;
+            // Reclaim the buffer to avoid an allocation on the next frame
+            self.scratch = rgb.into_raw_vec_and_offset().0;
+        }
+
+        Ok(())
+    }
+
+    /// Flush pending frames and close the file
+    pub fn finish(self) -> Result<(), CaptureError> {
+        #[cfg(windows)]
+        self.encoder.finish()?;
+
+        #[cfg(not(windows))]
+        {
+            let mut encoder = self.encoder;
+            encoder.finish()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,759 @@
+//! X11 screen grabbing for the Linux recorder backend
+//!
+//! Reads the root window through an MIT-SHM segment, falling back to core
+//! `GetImage` requests (which copy every frame over the socket) when the
+//! extension is missing or the server can't map the segment, e.g. over SSH.
+//! The cursor is composited from XFixes, since X11 screenshots never
+//! include it. Monitors come from RandR; all of them share one root window,
+//! so monitor and region targets are just areas of it. Connects to whatever
+//! `$DISPLAY` points at, so it works the same under Xvfb.
+
+use super::source::{
+    select_window, CaptureArea, CaptureError, CaptureSource, CapturedFrame, FrameSink, MonitorInfo,
+    PixelFormat, WindowInfo,
+};
+use crate::config::CaptureTarget;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as _;
+use x11rb::protocol::shm::{self, ConnectionExt as _};
+use x11rb::protocol::xfixes::ConnectionExt as _;
+use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt as _, ImageFormat, Window};
+use x11rb::rust_connection::RustConnection;
+
+/// Grabs frames from the root window of an X11 screen
+pub struct X11Grabber {
+    conn: RustConnection,
+    root: Window,
+    width: u32,
+    height: u32,
+    has_xfixes: bool,
+    /// Segment frames are grabbed into, `None` when grabbing with core `GetImage`
+    shm: Option<ShmSegment>,
+    /// Whether attaching `shm` was tried; it's only needed once grabbing starts
+    shm_tried: bool,
+}
+
+/// A System V shared memory segment the X server writes grabbed images into
+struct ShmSegment {
+    seg: shm::Seg,
+    addr: *const u8,
+    size: usize,
+}
+
+// The mapping is only read through the grabber that owns it
+unsafe impl Send for ShmSegment {}
+
+impl ShmSegment {
+    /// Create a `size`-byte segment and attach it to the server
+    fn attach(conn: &RustConnection, size: usize) -> Option<Self> {
+        conn.shm_query_version().ok()?.reply().ok()?;
+
+        // SAFETY: plain SysV calls on a private segment; `addr` is checked
+        // before use and unmapped again on every failure path
+        unsafe {
+            let id = libc::shmget(libc::IPC_PRIVATE, size, libc::IPC_CREAT | 0o600);
+            if id < 0 {
+                return None;
+            }
+            let addr = libc::shmat(id, std::ptr::null(), libc::SHM_RDONLY);
+            let attached = if addr as isize == -1 {
+                None
+            } else {
+                conn.generate_id().ok().filter(|&seg| {
+                    conn.shm_attach(seg, id as u32, false)
+                        .ok()
+                        .and_then(|cookie| cookie.check().ok())
+                        .is_some()
+                })
+            };
+            // The segment is freed once both sides have detached, even if we crash
+            libc::shmctl(id, libc::IPC_RMID, std::ptr::null_mut());
+
+            match attached {
+                Some(seg) => Some(Self {
+                    seg,
+                    addr: addr as *const u8,
+                    size,
+                }),
+                None => {
+                    if addr as isize != -1 {
+                        libc::shmdt(addr);
+                    }
+                    None
+                }
+            }
+        }
+    }
+
+    /// Detach from the server and unmap
+    fn release(self, conn: &RustConnection) {
+        let _ = conn.shm_detach(self.seg);
+        let _ = conn.flush();
+        // SAFETY: `addr` came from a successful `shmat` and isn't read after this
+        unsafe {
+            libc::shmdt(self.addr as *const libc::c_void);
+        }
+    }
+}
+
+/// Error for a screen whose pixels aren't 32 bits each
+fn unsupported_depth(depth: u8) -> CaptureError {
+    format!(
+        "Unsupported X11 depth {depth} (expected 24/32-bit TrueColor, e.g. Xvfb -screen 0 1920x1080x24)"
+    )
+    .into()
+}
+
+/// Convert TrueColor pixels, which come back as BGRX in little-endian order, to packed RGB24
+fn bgrx_to_rgb(bgrx: &[u8], rgb: &mut [u8]) {
+    for (src, dst) in bgrx.chunks_exact(4).zip(rgb.chunks_exact_mut(3)) {
+        dst[0] = src[2];
+        dst[1] = src[1];
+        dst[2] = src[0];
+    }
+}
+
+impl Drop for X11Grabber {
+    fn drop(&mut self) {
+        if let Some(shm) = self.shm.take() {
+            shm.release(&self.conn);
+        }
+    }
+}
+
+impl X11Grabber {
+    /// Connect to the display named by `$DISPLAY`
+    pub fn connect() -> Result<Self, CaptureError> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let screen = &conn.setup().roots[screen_num];
+        let root = screen.root;
+        let width = screen.width_in_pixels as u32;
+        let height = screen.height_in_pixels as u32;
+
+        // XFixes is only needed for the cursor overlay, so recording still works without it
+        let has_xfixes = conn
+            .xfixes_query_version(4, 0)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .is_some();
+        if !has_xfixes {
+            eprintln!("XFixes not available, cursor will not be drawn");
+        }
+
+        Ok(Self {
+            conn,
+            root,
+            width,
+            height,
+            has_xfixes,
+            shm: None,
+            shm_tried: false,
+        })
+    }
+
+    /// Screen width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Screen height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The whole root window
+    pub fn screen_area(&self) -> CaptureArea {
+        CaptureArea {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Clip an area to the screen so `GetImage` never reads outside the root window
+    pub fn clip(&self, area: CaptureArea) -> CaptureArea {
+        self.screen_area().intersect(&area).unwrap_or_default()
+    }
+
+    /// Active monitors reported by RandR 1.5, empty if the extension is missing
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        let reply = self
+            .conn
+            .randr_query_version(1, 5)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|_| self.conn.randr_get_monitors(self.root, true).ok())
+            .and_then(|cookie| cookie.reply().ok());
+        let Some(reply) = reply else {
+            return Vec::new();
+        };
+        let scale_factor = self.xft_scale();
+
+        reply
+            .monitors
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let name = self
+                    .conn
+                    .get_atom_name(m.name)
+                    .ok()
+                    .and_then(|cookie| cookie.reply().ok())
+                    .map(|reply| String::from_utf8_lossy(&reply.name).into_owned())
+                    .unwrap_or_else(|| format!("Monitor {}", i + 1));
+                MonitorInfo {
+                    name,
+                    area: CaptureArea {
+                        x: m.x as i32,
+                        y: m.y as i32,
+                        width: m.width as u32,
+                        height: m.height as u32,
+                    },
+                    primary: m.primary,
+                    scale_factor,
+                }
+            })
+            .collect()
+    }
+
+    /// Desktop scale from the `Xft.dpi` resource, 1.0 at 96 DPI
+    ///
+    /// X11 has no per-monitor scale; toolkits apply this one everywhere.
+    fn xft_scale(&self) -> f64 {
+        self.property_string(
+            self.root,
+            AtomEnum::RESOURCE_MANAGER.into(),
+            AtomEnum::STRING.into(),
+        )
+        .lines()
+        .find_map(|line| line.strip_prefix("Xft.dpi:"))
+        .and_then(|dpi| dpi.trim().parse::<f64>().ok())
+        .filter(|&dpi| dpi > 0.0)
+        .map_or(1.0, |dpi| dpi / 96.0)
+    }
+
+    fn atom(&self, name: &str) -> Result<Atom, CaptureError> {
+        Ok(self.conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+    }
+
+    /// Read a 32-bit list property (window ids, cardinals) from `window`
+    fn property_u32s(&self, window: Window, name: &str) -> Result<Vec<u32>, CaptureError> {
+        let atom = self.atom(name)?;
+        let reply = self
+            .conn
+            .get_property(false, window, atom, AtomEnum::ANY, 0, u32::MAX)?
+            .reply()?;
+        Ok(reply.value32().map(|v| v.collect()).unwrap_or_default())
+    }
+
+    /// Read a text property from `window`, empty if missing
+    fn property_string(&self, window: Window, property: Atom, type_: Atom) -> String {
+        self.conn
+            .get_property(false, window, property, type_, 0, u32::MAX)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| String::from_utf8_lossy(&reply.value).into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Currently focused top-level window, from `_NET_ACTIVE_WINDOW`
+    pub fn active_window(&self) -> Option<Window> {
+        self.property_u32s(self.root, "_NET_ACTIVE_WINDOW")
+            .ok()?
+            .first()
+            .copied()
+            .filter(|&w| w != 0)
+    }
+
+    /// Top-level windows managed by the window manager, from `_NET_CLIENT_LIST`
+    pub fn client_windows(&self) -> Vec<Window> {
+        self.property_u32s(self.root, "_NET_CLIENT_LIST")
+            .unwrap_or_default()
+    }
+
+    /// Process id that owns `window`, from `_NET_WM_PID`
+    pub fn window_pid(&self, window: Window) -> Option<u32> {
+        self.property_u32s(window, "_NET_WM_PID")
+            .ok()?
+            .first()
+            .copied()
+    }
+
+    /// Title and process name of `window`, or `None` if it has no title
+    pub fn window_info(&self, window: Window) -> Option<WindowInfo> {
+        let utf8_string = self.atom("UTF8_STRING").ok()?;
+        let net_wm_name = self.atom("_NET_WM_NAME").ok()?;
+
+        let mut title = self.property_string(window, net_wm_name, utf8_string);
+        if title.is_empty() {
+            title = self.property_string(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into());
+        }
+        if title.trim().is_empty() {
+            return None;
+        }
+
+        let process = self
+            .window_pid(window)
+            .and_then(|pid| std::fs::read_to_string(format!("/proc/{pid}/comm")).ok())
+            .map(|comm| comm.trim().to_string())
+            .unwrap_or_default();
+
+        Some(WindowInfo { title, process })
+    }
+
+    /// Description and screen bounds of the focused window
+    pub fn foreground_window(&self) -> Option<(WindowInfo, CaptureArea)> {
+        let window = self.active_window()?;
+        Some((self.window_info(window)?, self.window_bounds(window).ok()?))
+    }
+
+    /// Screen-space bounds of `window`, clipped to the screen
+    pub fn window_bounds(&self, window: Window) -> Result<CaptureArea, CaptureError> {
+        let geometry = self.conn.get_geometry(window)?.reply()?;
+        let origin = self
+            .conn
+            .translate_coordinates(window, self.root, 0, 0)?
+            .reply()?;
+        Ok(self.clip(CaptureArea {
+            x: origin.dst_x as i32,
+            y: origin.dst_y as i32,
+            width: geometry.width as u32,
+            height: geometry.height as u32,
+        }))
+    }
+
+    /// Grab a `width`x`height` area at (`x`, `y`) into `rgb` as packed RGB24
+    ///
+    /// If a shared memory grab fails the segment is dropped and this and
+    /// later frames use `GetImage`.
+    pub fn grab_rgb(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        rgb: &mut [u8],
+    ) -> Result<(), CaptureError> {
+        if !self.shm_tried {
+            self.shm_tried = true;
+            // Sized for the whole screen so any capture area fits
+            self.shm = ShmSegment::attach(&self.conn, (self.width * self.height * 4) as usize);
+            if self.shm.is_none() {
+                eprintln!("MIT-SHM not available, grabbing frames with GetImage");
+            }
+        }
+
+        let grabbed = match &self.shm {
+            Some(shm) => match self.grab_shm(shm, x, y, width, height) {
+                Ok(bgrx) => {
+                    bgrx_to_rgb(bgrx, rgb);
+                    true
+                }
+                Err(e) => {
+                    eprintln!("MIT-SHM grab failed, falling back to GetImage: {e}");
+                    false
+                }
+            },
+            None => false,
+        };
+        if !grabbed {
+            if let Some(shm) = self.shm.take() {
+                shm.release(&self.conn);
+            }
+            self.grab_core(x, y, width, height, rgb)?;
+        }
+
+        if self.has_xfixes {
+            self.draw_cursor(x, y, width, height, rgb);
+        }
+
+        Ok(())
+    }
+
+    /// Grab an area into the shared memory segment, returning its BGRX pixels
+    fn grab_shm<'a>(
+        &self,
+        shm: &'a ShmSegment,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Result<&'a [u8], CaptureError> {
+        let len = (width * height * 4) as usize;
+        if len > shm.size {
+            return Err("Capture area is larger than the shared memory segment".into());
+        }
+        let reply = self
+            .conn
+            .shm_get_image(
+                self.root,
+                x as i16,
+                y as i16,
+                width as u16,
+                height as u16,
+                !0,
+                ImageFormat::Z_PIXMAP.into(),
+                shm.seg,
+                0,
+            )?
+            .reply()?;
+        if reply.size as usize != len {
+            return Err(unsupported_depth(reply.depth));
+        }
+
+        // SAFETY: the server is done writing once the reply arrives, and the
+        // segment stays mapped while `shm` is borrowed
+        Ok(unsafe { std::slice::from_raw_parts(shm.addr, len) })
+    }
+
+    /// Grab an area with a core `GetImage` request
+    fn grab_core(
+        &self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        rgb: &mut [u8],
+    ) -> Result<(), CaptureError> {
+        let reply = self
+            .conn
+            .get_image(
+                ImageFormat::Z_PIXMAP,
+                self.root,
+                x as i16,
+                y as i16,
+                width as u16,
+                height as u16,
+                !0,
+            )?
+            .reply()?;
+
+        if reply.data.len() != (width * height * 4) as usize {
+            return Err(unsupported_depth(reply.depth));
+        }
+        bgrx_to_rgb(&reply.data, rgb);
+        Ok(())
+    }
+
+    /// Alpha-blend the current cursor image over a grabbed area
+    fn draw_cursor(&self, area_x: i32, area_y: i32, width: u32, height: u32, rgb: &mut [u8]) {
+        let cursor = match self
+            .conn
+            .xfixes_get_cursor_image()
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+        {
+            Some(cursor) => cursor,
+            None => return,
+        };
+
+        let origin_x = cursor.x as i32 - cursor.xhot as i32 - area_x;
+        let origin_y = cursor.y as i32 - cursor.yhot as i32 - area_y;
+
+        for cy in 0..cursor.height as i32 {
+            let py = origin_y + cy;
+            if py < 0 || py >= height as i32 {
+                continue;
+            }
+            for cx in 0..cursor.width as i32 {
+                let px = origin_x + cx;
+                if px < 0 || px >= width as i32 {
+                    continue;
+                }
+
+                // Cursor pixels are premultiplied ARGB
+                let argb = cursor.cursor_image[(cy * cursor.width as i32 + cx) as usize];
+                let alpha = (argb >> 24) & 0xff;
+                if alpha == 0 {
+                    continue;
+                }
+                let inv = 255 - alpha;
+                let idx = (py as usize * width as usize + px as usize) * 3;
+                let src = [(argb >> 16) & 0xff, (argb >> 8) & 0xff, argb & 0xff];
+                for (dst, src) in rgb[idx..idx + 3].iter_mut().zip(src) {
+                    *dst = (src + *dst as u32 * inv / 255).min(255) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// List titled top-level windows on the X11 display
+pub fn list_windows() -> Vec<WindowInfo> {
+    match X11Grabber::connect() {
+        Ok(grabber) => grabber
+            .client_windows()
+            .into_iter()
+            .filter_map(|w| grabber.window_info(w))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// List monitors on the X11 display
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    X11Grabber::connect()
+        .map(|grabber| grabber.monitors())
+        .unwrap_or_default()
+}
+
+/// Polls an [`X11Grabber`] for a fixed screen area at a fixed frame rate
+pub struct X11Source {
+    grabber: Option<X11Grabber>,
+    area: CaptureArea,
+    fps: u32,
+    stop_flag: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl X11Source {
+    /// Source for a configured capture target, sampled at `fps`
+    ///
+    /// Window targets record the window's screen area as it was when
+    /// recording started.
+    pub fn for_target(target: &CaptureTarget, fps: u32) -> Result<Self, CaptureError> {
+        let grabber = X11Grabber::connect()?;
+        println!(
+            "Screen resolution: {}x{}",
+            grabber.width(),
+            grabber.height()
+        );
+
+        let area = match target {
+            // Without RandR (or with no primary set) the whole screen stands in
+            CaptureTarget::PrimaryMonitor => grabber
+                .monitors()
+                .into_iter()
+                .find(|m| m.primary)
+                .map(|m| grabber.clip(m.area))
+                .unwrap_or_else(|| grabber.screen_area()),
+            CaptureTarget::Monitor(index) => {
+                let monitors = grabber.monitors();
+                match monitors.get(*index) {
+                    Some(monitor) => grabber.clip(monitor.area),
+                    // A plain Xvfb screen reports no monitors; treat it as the only one
+                    None if monitors.is_empty() && *index == 0 => grabber.screen_area(),
+                    None => return Err(format!("Monitor {} not found", index + 1).into()),
+                }
+            }
+            CaptureTarget::Region { x, y, w, h } => grabber.clip(CaptureArea {
+                x: *x,
+                y: *y,
+                width: *w,
+                height: *h,
+            }),
+            CaptureTarget::ForegroundWindow => {
+                let window = grabber
+                    .active_window()
+                    .ok_or("No active window (is a window manager running?)")?;
+                // Clicking "Record" leaves our own window in front; only the hotkey makes sense here
+                if grabber.window_pid(window) == Some(std::process::id()) {
+                    return Err(
+                        "DemoRecorder is the foreground window; use the hotkey from the window you want to record"
+                            .into(),
+                    );
+                }
+                grabber.window_bounds(window)?
+            }
+            CaptureTarget::Window { title, process } => {
+                let windows: Vec<(Window, WindowInfo)> = grabber
+                    .client_windows()
+                    .into_iter()
+                    .filter_map(|w| grabber.window_info(w).map(|info| (w, info)))
+                    .collect();
+                let infos: Vec<WindowInfo> = windows.iter().map(|(_, info)| info.clone()).collect();
+                let index = select_window(&infos, title.as_deref(), process.as_deref())
+                    .ok_or("No open window matches the selected capture window")?;
+                grabber.window_bounds(windows[index].0)?
+            }
+        };
+
+        Self::with_area(grabber, area, fps)
+    }
+
+    fn with_area(
+        grabber: X11Grabber,
+        mut area: CaptureArea,
+        fps: u32,
+    ) -> Result<Self, CaptureError> {
+        // H.264 with yuv420p needs even dimensions
+        area.width &= !1;
+        area.height &= !1;
+        if area.width == 0 || area.height == 0 {
+            return Err("Capture area is empty".into());
+        }
+
+        println!(
+            "Capture area: {}x{} at ({}, {})",
+            area.width, area.height, area.x, area.y
+        );
+
+        Ok(Self {
+            grabber: Some(grabber),
+            area,
+            fps: fps.max(1),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            thread: None,
+        })
+    }
+}
+
+impl CaptureSource for X11Source {
+    fn resolution(&self) -> (u32, u32) {
+        (self.area.width, self.area.height)
+    }
+
+    fn origin(&self) -> (i32, i32) {
+        (self.area.x, self.area.y)
+    }
+
+    fn pixel_format(&self) -> PixelFormat {
+        PixelFormat::Rgb8
+    }
+
+    fn start(&mut self, mut sink: Box<dyn FrameSink>) -> Result<(), CaptureError> {
+        let mut grabber = self
+            .grabber
+            .take()
+            .ok_or("X11 source has already been started")?;
+        let area = self.area;
+        let (width, height) = (area.width, area.height);
+        let frame_interval = Duration::from_secs_f64(1.0 / self.fps as f64);
+        let stop_flag = self.stop_flag.clone();
+
+        self.thread = Some(std::thread::spawn(move || {
+            let start = Instant::now();
+            let mut next_frame = start;
+            let mut rgb = vec![0u8; (width * height * 3) as usize];
+
+            while !stop_flag.load(Ordering::SeqCst) {
+                if let Err(e) = grabber.grab_rgb(area.x, area.y, width, height, &mut rgb) {
+                    eprintln!("Capture error: {e}");
+                    break;
+                }
+
+                // Timestamp by wall clock so slow grabs don't speed up playback
+                let frame = CapturedFrame {
+                    data: &rgb,
+                    width,
+                    height,
+                    format: PixelFormat::Rgb8,
+                    timestamp: start.elapsed(),
+                };
+                match sink.on_frame(frame) {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    Err(e) => {
+                        eprintln!("Capture error: {e}");
+                        break;
+                    }
+                }
+
+                next_frame += frame_interval;
+                let now = Instant::now();
+                if next_frame > now {
+                    std::thread::sleep(next_frame - now);
+                } else {
+                    // Running behind - skip ahead instead of bursting frames
+                    next_frame = now;
+                }
+            }
+
+            if let Err(e) = sink.on_stopped() {
+                eprintln!("Capture error: {e}");
+            }
+        }));
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// Passes the first frame's size and format back to the test, then stops
+    struct FirstFrame(mpsc::Sender<(u32, u32, PixelFormat, usize)>);
+
+    impl FrameSink for FirstFrame {
+        fn on_frame(&mut self, frame: CapturedFrame<'_>) -> Result<bool, CaptureError> {
+            let _ = self
+                .0
+                .send((frame.width, frame.height, frame.format, frame.data.len()));
+            Ok(false)
+        }
+
+        fn on_stopped(&mut self) -> Result<(), CaptureError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[ignore = "needs an X server: xvfb-run -s '-screen 0 640x480x24' cargo test -- --ignored"]
+    fn grabs_root_window() {
+        if std::env::var_os("DISPLAY").is_none() {
+            eprintln!("DISPLAY not set, skipping");
+            return;
+        }
+        let mut grabber = X11Grabber::connect().expect("connect to $DISPLAY");
+        let (width, height) = (grabber.width(), grabber.height());
+        assert!(width > 0 && height > 0);
+
+        let mut shm_rgb = vec![0u8; (width * height * 3) as usize];
+        grabber
+            .grab_rgb(0, 0, width, height, &mut shm_rgb)
+            .expect("grab with MIT-SHM");
+        assert!(grabber.shm.is_some(), "Xvfb supports MIT-SHM");
+
+        // Both paths must produce the same pixels
+        grabber.shm.take().unwrap().release(&grabber.conn);
+        let mut core_rgb = vec![0u8; shm_rgb.len()];
+        grabber
+            .grab_rgb(0, 0, width, height, &mut core_rgb)
+            .expect("grab with GetImage");
+        assert!(shm_rgb == core_rgb, "MIT-SHM and GetImage grabs differ");
+    }
+
+    #[test]
+    #[ignore = "needs an X server: xvfb-run -s '-screen 0 640x480x24' cargo test -- --ignored"]
+    fn source_delivers_rgb_frames() {
+        if std::env::var_os("DISPLAY").is_none() {
+            eprintln!("DISPLAY not set, skipping");
+            return;
+        }
+        let mut source =
+            X11Source::for_target(&CaptureTarget::PrimaryMonitor, 30).expect("open X11 source");
+        let (width, height) = source.resolution();
+        assert_eq!(source.pixel_format(), PixelFormat::Rgb8);
+
+        let (tx, rx) = mpsc::channel();
+        source.start(Box::new(FirstFrame(tx))).expect("start");
+        let frame = rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("a frame within 5s");
+        source.stop();
+
+        assert_eq!(
+            frame,
+            (
+                width,
+                height,
+                PixelFormat::Rgb8,
+                (width * height * 3) as usize
+            )
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Countdown shown in its own always-on-top window before recording starts
+//!
+//! The overlay runs in a separate window (and VirtualDom), so it reads the
+//! remaining seconds from [`shared_state`] instead of a signal and closes
+//! itself when the countdown ends or is cancelled.
+
+use crate::capture::list_monitors;
+use crate::shared_state;
+use dioxus::desktop::tao::dpi::PhysicalPosition;
+use dioxus::desktop::{Config, LogicalSize, WindowBuilder};
+use dioxus::prelude::*;
+
+/// Overlay window edge length in logical pixels
+const OVERLAY_SIZE: f64 = 220.0;
+
+/// Open the countdown overlay centred on the primary monitor
+///
+/// The window never takes focus, so "Foreground Window" capture still picks
+/// the window the presenter was using.
+pub fn open_countdown_overlay() {
+    let mut window = WindowBuilder::new()
+        .with_title("DemoRecorder countdown")
+        .with_inner_size(LogicalSize::new(OVERLAY_SIZE, OVERLAY_SIZE))
+        .with_decorations(false)
+        .with_transparent(true)
+        .with_resizable(false)
+        .with_always_on_top(true)
+        .with_focused(false);
+
+    if let Some(monitor) = list_monitors().into_iter().find(|m| m.primary) {
+        let area = monitor.area;
+        window = window.with_position(PhysicalPosition::new(
+            area.x + area.width as i32 / 2 - OVERLAY_SIZE as i32 / 2,
+            area.y + area.height as i32 / 2 - OVERLAY_SIZE as i32 / 2,
+        ));
+    }
+
+    let config = Config::new()
+        .with_window(window)
+        .with_menu(None)
+        .with_background_color((0, 0, 0, 0));
+    let _ = dioxus::desktop::window().new_window(VirtualDom::new(CountdownOverlay), config);
+}
+
+/// Big countdown number, polled from shared state
+#[component]
+pub fn CountdownOverlay() -> Element {
+    let mut remaining = use_signal(shared_state::countdown_remaining);
+
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+
+            let now = shared_state::countdown_remaining();
+            if now == 0 {
+                // Finished or cancelled: get out of the way before capture starts
+                dioxus::desktop::window().close();
+                break;
+            }
+            if now != remaining() {
+                remaining.set(now);
+            }
+        }
+    });
+
+    rsx! {
+        div {
+            style: "width: 100vw; height: 100vh; display: flex; flex-direction: column; align-items: center; justify-content: center; border-radius: 50%; background: rgba(17, 24, 39, 0.85); color: white; font-family: system-ui, sans-serif; user-select: none;",
+            div { style: "font-size: 96px; font-weight: 700; line-height: 1;", "{remaining}" }
+            div { style: "margin-top: 12px; font-size: 12px; color: #9ca3af;", "Press the hotkey to cancel" }
+        }
+    }
+}
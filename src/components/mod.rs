@@ -1,3 +1,4 @@
 //! Reusable components
 
-// Components can be added here as needed
+mod countdown;
+pub use countdown::open_countdown_overlay;
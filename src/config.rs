@@ -22,37 +22,109 @@ pub enum AudioMode {
     Both,
 }
 
-/// Zoom behavior mode
+/// How the 🔍 button moves the camera
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 pub enum ZoomMode {
-    #[default]
+    /// No zoom; the 🔍 button leaves recordings as they are
     None,
+    /// Hold the zoom level and follow the cursor
     FollowCursor,
+    /// Zoom in on clicks (or wherever the triggers say) and back out
+    #[default]
     ClickToZoom,
+    /// Zooms like [`ZoomMode::ClickToZoom`] for now
     SmartAI,
 }
 
-/// What to capture
+/// How key presses are written to the event log
+///
+/// Typed characters are never stored by name.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum KeyLogging {
+    /// No key events at all
+    Off,
+    /// Shortcuts and special keys by name, typing and editing keys only as a category
+    ///
+    /// Configs from builds that could log every key by name load as this.
+    #[default]
+    #[serde(other)]
+    Categories,
+}
+
+/// What makes the camera zoom in, outside of follow-cursor mode
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+pub enum ZoomTriggers {
+    #[default]
+    Clicks,
+    /// The cursor slowing down and resting somewhere after moving there
+    CursorSettle,
+    Both,
+}
+
+/// What to capture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
 pub enum CaptureTarget {
     #[default]
     PrimaryMonitor,
     ForegroundWindow,
+    /// A specific window, matched by title substring and/or process name
+    Window {
+        title: Option<String>,
+        process: Option<String>,
+    },
+    /// A monitor by its position in the platform's monitor list (0-based)
+    Monitor(usize),
+    /// A fixed rectangle in virtual-screen coordinates
+    Region {
+        x: i32,
+        y: i32,
+        w: u32,
+        h: u32,
+    },
+}
+
+impl CaptureTarget {
+    /// Variant name without payload, used as the Settings select value
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CaptureTarget::PrimaryMonitor => "PrimaryMonitor",
+            CaptureTarget::ForegroundWindow => "ForegroundWindow",
+            CaptureTarget::Window { .. } => "Window",
+            CaptureTarget::Monitor(_) => "Monitor",
+            CaptureTarget::Region { .. } => "Region",
+        }
+    }
 }
 
 /// Application configuration
+///
+/// Fields missing from an older config file take their default values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     pub hotkey: String,
     pub output_format: OutputFormat,
     pub audio_mode: AudioMode,
     pub zoom_mode: ZoomMode,
     pub zoom_level: f32,
+    pub zoom_triggers: ZoomTriggers,
     pub output_folder: PathBuf,
     pub fps: u32,
     pub show_countdown: bool,
     pub countdown_seconds: u32,
     pub capture_target: CaptureTarget,
+    /// Recording file name without extension; see [`Config::recording_stem`]
+    pub filename_template: String,
+    /// Stop recordings after this many seconds (0 = no limit)
+    pub max_duration_secs: u64,
+    /// Keep this many seconds of screen in memory for instant replay (0 = off)
+    pub replay_buffer_secs: u32,
+    /// How many times a second mouse and keyboard input is sampled while logging
+    pub input_sample_hz: u32,
+    pub key_logging: KeyLogging,
+    /// Keys aren't logged at all while a window whose title or app contains
+    /// one of these (case-insensitive) is in front
+    pub sensitive_windows: Vec<String>,
 }
 
 impl Default for Config {
@@ -65,13 +137,35 @@ impl Default for Config {
             hotkey: "Ctrl+Shift+F9".to_string(),
             output_format: OutputFormat::default(),
             audio_mode: AudioMode::default(),
-            zoom_mode: ZoomMode::None,
+            zoom_mode: ZoomMode::default(),
             zoom_level: 1.5,
+            zoom_triggers: ZoomTriggers::default(),
             output_folder,
             fps: 30,
             show_countdown: true,
             countdown_seconds: 3,
             capture_target: CaptureTarget::default(),
+            filename_template: "recording_{timestamp}".to_string(),
+            max_duration_secs: 0,
+            replay_buffer_secs: 0,
+            input_sample_hz: 120,
+            key_logging: KeyLogging::default(),
+            sensitive_windows: [
+                "password",
+                "passwort",
+                "sign in",
+                "log in",
+                "login",
+                "unlock",
+                "credential",
+                "keychain",
+                "1password",
+                "bitwarden",
+                "keepass",
+                "lastpass",
+            ]
+            .map(String::from)
+            .to_vec(),
         }
     }
 }
@@ -108,6 +202,56 @@ impl Config {
         Ok(())
     }
 
+    /// File name (without extension) for a recording started at `unix_secs`
+    ///
+    /// `{timestamp}` expands to Unix seconds, `{date}` to `YYYYMMDD` and
+    /// `{time}` to `HHMMSS` (both UTC). Characters that aren't valid in file
+    /// names, and dots (which would confuse the `.events.bin` pairing), become `_`.
+    pub fn recording_stem(&self, unix_secs: u64) -> String {
+        let days = (unix_secs / 86_400) as i64;
+        let secs_of_day = unix_secs % 86_400;
+
+        // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        let stem = self
+            .filename_template
+            .replace("{timestamp}", &unix_secs.to_string())
+            .replace("{date}", &format!("{year:04}{month:02}{day:02}"))
+            .replace(
+                "{time}",
+                &format!(
+                    "{:02}{:02}{:02}",
+                    secs_of_day / 3600,
+                    secs_of_day / 60 % 60,
+                    secs_of_day % 60
+                ),
+            );
+        let stem: String = stem
+            .trim()
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '.' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+
+        if stem.is_empty() {
+            format!("recording_{unix_secs}")
+        } else {
+            stem
+        }
+    }
+
     fn config_path() -> PathBuf {
         dirs::config_dir()
             .unwrap_or_else(|| dirs::home_dir().unwrap_or_default())
@@ -115,3 +259,25 @@ impl Config {
             .join("config.json")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zoom_modes_load_as_saved() {
+        for mode in [
+            ZoomMode::None,
+            ZoomMode::FollowCursor,
+            ZoomMode::ClickToZoom,
+            ZoomMode::SmartAI,
+        ] {
+            let json = format!("\"{mode:?}\"");
+            assert_eq!(serde_json::from_str::<ZoomMode>(&json).unwrap(), mode);
+        }
+        assert_eq!(
+            serde_json::to_string(&ZoomMode::default()).unwrap(),
+            "\"ClickToZoom\""
+        );
+    }
+}
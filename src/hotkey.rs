@@ -1,6 +1,7 @@
 //! Global hotkey management for the screen recorder
 //!
-//! Default hotkey: Ctrl+Shift+F9
+//! Default hotkeys: Ctrl+Shift+F9 (start/stop), Ctrl+Shift+F10 (pause/resume),
+//! Ctrl+Shift+F11 (save instant replay), Ctrl+Shift+F12 (add chapter marker)
 
 use crate::shared_state;
 use global_hotkey::{
@@ -12,6 +13,10 @@ use global_hotkey::{
 pub struct HotkeyManager {
     _manager: GlobalHotKeyManager,
     toggle_recording_id: u32,
+    /// Unset when another app already owns the hotkey
+    pause_recording_id: Option<u32>,
+    save_replay_id: Option<u32>,
+    add_marker_id: Option<u32>,
 }
 
 impl HotkeyManager {
@@ -22,14 +27,20 @@ impl HotkeyManager {
         // Ctrl+Shift+F9 for toggle recording
         let toggle_hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), Code::F9);
         let toggle_recording_id = toggle_hotkey.id();
-
         manager.register(toggle_hotkey)?;
-
         println!("Registered hotkey: Ctrl+Shift+F9 for toggle recording");
 
+        // The rest are optional: recording still works if another app owns them
+        let pause_recording_id = register_optional(&manager, Code::F10, "pause/resume");
+        let save_replay_id = register_optional(&manager, Code::F11, "save replay");
+        let add_marker_id = register_optional(&manager, Code::F12, "add marker");
+
         Ok(Self {
             _manager: manager,
             toggle_recording_id,
+            pause_recording_id,
+            save_replay_id,
+            add_marker_id,
         })
     }
 
@@ -37,10 +48,48 @@ impl HotkeyManager {
     pub fn toggle_id(&self) -> u32 {
         self.toggle_recording_id
     }
+
+    /// Get the pause/resume hotkey ID, if it could be registered
+    pub fn pause_id(&self) -> Option<u32> {
+        self.pause_recording_id
+    }
+
+    /// Get the save replay hotkey ID, if it could be registered
+    pub fn replay_id(&self) -> Option<u32> {
+        self.save_replay_id
+    }
+
+    /// Get the add marker hotkey ID, if it could be registered
+    pub fn marker_id(&self) -> Option<u32> {
+        self.add_marker_id
+    }
+}
+
+/// Register Ctrl+Shift+`code`, returning its ID, or `None` with a warning
+/// if it can't be registered
+fn register_optional(manager: &GlobalHotKeyManager, code: Code, action: &str) -> Option<u32> {
+    let hotkey = HotKey::new(Some(Modifiers::CONTROL | Modifiers::SHIFT), code);
+    match manager.register(hotkey) {
+        Ok(()) => {
+            println!("Registered hotkey: Ctrl+Shift+{code:?} for {action}");
+            Some(hotkey.id())
+        }
+        Err(e) => {
+            eprintln!("Failed to register Ctrl+Shift+{code:?} for {action}: {e}");
+            None
+        }
+    }
 }
 
 /// Start the hotkey listener in a background thread
-pub fn start_hotkey_listener(toggle_id: u32) {
+///
+/// Hotkeys whose ID is `None` weren't registered and are ignored.
+pub fn start_hotkey_listener(
+    toggle_id: u32,
+    pause_id: Option<u32>,
+    replay_id: Option<u32>,
+    marker_id: Option<u32>,
+) {
     std::thread::spawn(move || {
         let receiver = GlobalHotKeyEvent::receiver();
         println!("Hotkey listener started");
@@ -48,8 +97,17 @@ pub fn start_hotkey_listener(toggle_id: u32) {
         loop {
             if let Ok(event) = receiver.recv() {
                 // Only trigger on key PRESS, not release
-                if event.id == toggle_id && event.state == HotKeyState::Pressed {
+                if event.state != HotKeyState::Pressed {
+                    continue;
+                }
+                if event.id == toggle_id {
                     shared_state::request_hotkey_toggle();
+                } else if Some(event.id) == pause_id {
+                    shared_state::request_hotkey_pause();
+                } else if Some(event.id) == replay_id {
+                    shared_state::request_replay_save();
+                } else if Some(event.id) == marker_id {
+                    shared_state::request_marker();
                 }
             }
         }
@@ -9,11 +9,17 @@ mod tray;
 mod views;
 mod zoom;
 
-use capture::{start_recording, stop_recording, RecorderConfig};
+use capture::{
+    request_replay_save, set_paused, start_recording, start_replay, stop_recording, stop_replay,
+    take_duration_warning, take_replay_saved, CaptureArea, RecorderConfig, REPLAY_SEGMENT_SECS,
+};
 use config::Config;
 use hotkey::HotkeyManager;
 use views::{Dashboard, Navbar, Settings};
-use zoom::{start_event_logging, stop_event_logging, update_event_logging};
+use zoom::{
+    add_marker, pause_event_logging, resume_event_logging, set_event_journal, set_event_retention,
+    snapshot_event_log, start_event_logging, stop_event_logging, RedactionPolicy,
+};
 
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
@@ -29,6 +35,26 @@ const FAVICON: Asset = asset!("/assets/favicon.ico");
 const MAIN_CSS: Asset = asset!("/assets/styling/main.css");
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
 
+/// Pause between the countdown overlay closing and capture starting
+const OVERLAY_CLOSE_GRACE: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Event log metadata for a recording of `area` at `fps`
+fn recording_metadata(area: CaptureArea, fps: u32) -> zoom::RecordingMetadata {
+    zoom::RecordingMetadata {
+        width: area.width,
+        height: area.height,
+        origin_x: area.x,
+        origin_y: area.y,
+        scale_factor: capture::scale_factor_at(area),
+        fps,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        // Filled in by the logger once the capture clock has started
+        started_at_ms: 0,
+        capture_delay_ms: 0,
+        clock_offset_ms: 0,
+    }
+}
+
 fn main() {
     // Initialize diagnostics
     let _guard = zoom::diagnostics::init_diagnostics();
@@ -44,11 +70,22 @@ fn main() {
         eprintln!("Failed to create output folder: {e}");
     }
 
+    // Stitch together recordings cut short by a crash
+    let recovered = capture::recover_sessions(&config.output_folder);
+    if !recovered.is_empty() {
+        println!("Recovered {} interrupted recording(s)", recovered.len());
+    }
+
     // Initialize hotkey manager and start listener
     match HotkeyManager::new() {
         Ok(hm) => {
             println!("Hotkeys registered successfully");
-            hotkey::start_hotkey_listener(hm.toggle_id());
+            hotkey::start_hotkey_listener(
+                hm.toggle_id(),
+                hm.pause_id(),
+                hm.replay_id(),
+                hm.marker_id(),
+            );
             // Keep the manager alive by leaking it (it needs to stay alive for hotkeys to work)
             Box::leak(Box::new(hm));
         }
@@ -77,19 +114,204 @@ fn main() {
 fn App() -> Element {
     // Recording state
     let mut is_rec = use_signal(|| false);
+    let mut is_paused = use_signal(|| false);
+    // Chapter markers added to the current recording
+    let mut marker_count = use_signal(|| 0u32);
     let mut status_message = use_signal(|| "Ready".to_string());
-    let mut current_events_path = use_signal(|| None::<std::path::PathBuf>);
+    let mut current_recording = use_signal(|| None::<std::path::PathBuf>);
     let mut saved_at = use_signal(|| None::<std::time::Instant>);
+    let mut countdown_until = use_signal(|| None::<std::time::Instant>);
+    // Area covered by the instant replay buffer while it is running
+    let mut replaying = use_signal(|| None::<CaptureArea>);
+
+    // Start the instant replay buffer if it is enabled in settings
+    let mut start_replay_buffer = move || {
+        let config = Config::load();
+        if config.replay_buffer_secs == 0 {
+            return;
+        }
+        match start_replay(&config) {
+            Ok(area) => {
+                // The buffer keeps whole segments, so keep events a little longer
+                start_event_logging(
+                    recording_metadata(area, config.fps),
+                    config.input_sample_hz,
+                    RedactionPolicy::from_config(&config),
+                );
+                set_event_retention(Some(std::time::Duration::from_secs(
+                    config.replay_buffer_secs as u64 + 2 * REPLAY_SEGMENT_SECS,
+                )));
+                replaying.set(Some(area));
+            }
+            Err(e) => {
+                replaying.set(None);
+                eprintln!("Failed to start instant replay: {e}");
+            }
+        }
+    };
+
+    // Stop the instant replay buffer, discarding what it held
+    let mut stop_replay_buffer = move || {
+        if replaying().is_some() {
+            stop_replay();
+            stop_event_logging();
+            replaying.set(None);
+        }
+    };
+
+    // Start recording with the current settings
+    let mut begin_recording = move |trigger: &'static str| {
+        // A recording replaces the replay buffer until it stops
+        stop_replay_buffer();
+        let settings = Config::load();
+        let config = RecorderConfig::from_config(&settings);
+        let events_path = config.events_path.clone();
+        let journal_path = config.parts_dir.join(zoom::EVENT_JOURNAL_FILE);
+
+        match start_recording(config) {
+            Ok(area) => {
+                // Log events relative to the captured area so they line up with the video
+                start_event_logging(
+                    recording_metadata(area, settings.fps),
+                    settings.input_sample_hz,
+                    RedactionPolicy::from_config(&settings),
+                );
+                if let Err(e) = set_event_journal(&journal_path) {
+                    eprintln!("Failed to start event journal: {e}");
+                }
+                current_recording.set(Some(events_path));
+
+                tracing::info!("Recording started ({trigger})");
+                is_rec.set(true);
+                status_message.set("Recording...".to_string());
+                marker_count.set(0);
+                println!(
+                    "{trigger}: Recording started at {}x{}",
+                    area.width, area.height
+                );
+            }
+            Err(e) => {
+                status_message.set(format!("Error: {}", e));
+                eprintln!("Failed to start recording: {e}");
+            }
+        }
+    };
+
+    // Start (after the countdown) or stop recording; shared by the hotkey poller and the button
+    let mut toggle_recording = move |trigger: &'static str| {
+        if is_rec() {
+            // Stop recording and save events
+            tracing::info!("Stopping recording ({trigger})...");
+            let event_log = stop_event_logging();
+            stop_recording();
+            is_rec.set(false);
+            is_paused.set(false);
+
+            // Save events to file
+            if let (Some(path), Some(event_log)) = (current_recording(), event_log) {
+                let event_count = event_log.events.len();
+                if let Err(e) = zoom::save_event_log(&event_log, &path) {
+                    eprintln!("Failed to save events: {e}");
+                } else {
+                    println!("Events saved to: {:?}", path);
+                }
+
+                status_message.set(format!("✓ Saved {} events", event_count));
+                println!(
+                    "{trigger}: Recording stopped, {} events captured",
+                    event_count
+                );
+            }
+            current_recording.set(None);
+
+            // Set a timestamp for auto-reset (handled in the polling loop)
+            saved_at.set(Some(std::time::Instant::now()));
+            start_replay_buffer();
+        } else if countdown_until().is_some() {
+            // Pressing the hotkey again during the countdown cancels it
+            countdown_until.set(None);
+            shared_state::set_countdown_remaining(0);
+            status_message.set("Countdown cancelled".to_string());
+            saved_at.set(Some(std::time::Instant::now()));
+            println!("{trigger}: Countdown cancelled");
+        } else {
+            let config = Config::load();
+            if config.show_countdown && config.countdown_seconds > 0 {
+                countdown_until.set(Some(
+                    std::time::Instant::now()
+                        + std::time::Duration::from_secs(config.countdown_seconds as u64),
+                ));
+                shared_state::set_countdown_remaining(config.countdown_seconds);
+                components::open_countdown_overlay();
+                status_message.set(format!("Starting in {}...", config.countdown_seconds));
+                saved_at.set(None);
+                println!(
+                    "{trigger}: Countdown started ({}s)",
+                    config.countdown_seconds
+                );
+            } else {
+                begin_recording(trigger);
+            }
+        }
+    };
+
+    // Pause or resume the recording in progress
+    let mut toggle_pause = move |trigger: &'static str| {
+        if !is_rec() {
+            return;
+        }
+        let paused = !is_paused();
+        // Recorder and event log cut the same wall-clock interval from their timelines
+        set_paused(paused);
+        if paused {
+            pause_event_logging();
+            status_message.set("Paused".to_string());
+        } else {
+            resume_event_logging();
+            status_message.set("Recording...".to_string());
+        }
+        is_paused.set(paused);
+        println!(
+            "{trigger}: Recording {}",
+            if paused { "paused" } else { "resumed" }
+        );
+    };
 
     // Poll for hotkey toggle requests and update event logging
     use_future(move || async move {
+        // Replay buffer length last applied, checked against settings about once a second
+        let mut replay_secs_applied = None::<u32>;
+        let mut ticks = 0u32;
         loop {
-            // Check every 10ms for hotkey toggle and update event logging for smoothness
+            // Check every 10ms for hotkey toggle (input is sampled on its own thread)
             tokio::time::sleep(std::time::Duration::from_millis(10)).await;
 
-            // Update event logging if recording
-            if is_rec() {
-                update_event_logging();
+            // Start, stop or resize the replay buffer when its setting changes
+            ticks = ticks.wrapping_add(1);
+            if ticks % 100 == 1 && !is_rec() && countdown_until().is_none() {
+                let secs = Config::load().replay_buffer_secs;
+                if replay_secs_applied != Some(secs) {
+                    replay_secs_applied = Some(secs);
+                    stop_replay_buffer();
+                    start_replay_buffer();
+                }
+            }
+
+            // Run the countdown, then give the overlay a moment to close so it isn't recorded
+            if let Some(deadline) = countdown_until() {
+                let now = std::time::Instant::now();
+                if now >= deadline + OVERLAY_CLOSE_GRACE {
+                    countdown_until.set(None);
+                    begin_recording("Countdown");
+                } else if now >= deadline {
+                    shared_state::set_countdown_remaining(0);
+                } else {
+                    let secs = (deadline - now).as_secs_f32().ceil() as u32;
+                    if secs != shared_state::countdown_remaining() {
+                        shared_state::set_countdown_remaining(secs);
+                        status_message.set(format!("Starting in {secs}..."));
+                    }
+                }
             }
 
             // Auto-reset status message after 3 seconds
@@ -102,129 +324,72 @@ fn App() -> Element {
                 saved_at.set(None);
             }
 
-            if shared_state::take_hotkey_toggle() {
-                let currently_recording = is_rec();
-                if currently_recording {
-                    // Stop recording and save events
-                    tracing::info!("Stopping recording (Hotkey)...");
-                    let events = stop_event_logging();
-                    stop_recording();
-                    is_rec.set(false);
-
-                    // Save events to file
-                    if let Some(ref path) = *current_events_path.read() {
-                        let event_count = events.len();
-                        let monitor = windows_capture::monitor::Monitor::primary().unwrap();
-                        let event_log = zoom::EventLog {
-                            metadata: zoom::RecordingMetadata {
-                                width: monitor.width().unwrap_or(1920),
-                                height: monitor.height().unwrap_or(1080),
-                            },
-                            events,
-                        };
-                        if let Err(e) = zoom::save_event_log(&event_log, path) {
-                            eprintln!("Failed to save events: {e}");
-                        } else {
-                            println!("Events saved to: {:?}", path);
-                        }
-
-                        status_message.set(format!("✓ Saved {} events", event_count));
-                        println!("Hotkey: Recording stopped, {} events captured", event_count);
-                    }
+            // The recorder stops on its own at the max duration (or on a capture error);
+            // finish the session so the event log is still saved
+            if is_rec() && !capture::is_recording() {
+                toggle_recording("Auto-stop");
+            }
+            if take_duration_warning() {
+                status_message.set("⚠ Max duration reached soon".to_string());
+            }
 
-                    // Set a timestamp for auto-reset (handled in the polling loop)
-                    saved_at.set(Some(std::time::Instant::now()));
+            if shared_state::take_hotkey_toggle() {
+                toggle_recording("Hotkey");
+            }
+            if shared_state::take_hotkey_pause() {
+                toggle_pause("Hotkey");
+            }
+            if shared_state::take_marker_request() {
+                // Markers only make sense on running video
+                if is_rec() && !is_paused() {
+                    marker_count += 1;
+                    let label = format!("Chapter {}", marker_count());
+                    add_marker(label.clone());
+                    status_message.set(format!("Recording... 📍 {label}"));
                 } else {
-                    // Start recording and event logging
-                    let monitor = windows_capture::monitor::Monitor::primary().unwrap();
-                    let width = monitor.width().unwrap_or(1920);
-                    let height = monitor.height().unwrap_or(1080);
-
-                    start_event_logging(width, height);
-                    let mut config = RecorderConfig::default();
-                    config.width = width;
-                    config.height = height;
-
-                    current_events_path.set(Some(config.events_path.clone()));
-
-                    match start_recording(config) {
-                        Ok(_) => {
-                            tracing::info!("Recording started (Hotkey)");
-                            is_rec.set(true);
-                            status_message.set("Recording...".to_string());
-                            println!("Hotkey: Recording started");
-                        }
-                        Err(e) => {
-                            stop_event_logging(); // Clean up
-                            current_events_path.set(None);
-                            status_message.set(format!("Error: {}", e));
-                            eprintln!("Failed to start recording: {e}");
-                        }
-                    }
+                    println!("Markers can only be added while recording");
                 }
             }
-        }
-    });
-
-    // Toggle recording function (for button click)
-    let toggle_recording = move |_| {
-        let currently_recording = is_rec();
-        if currently_recording {
-            // Stop recording and event logging
-            tracing::info!("Stopping recording (UI)...");
-            let events = stop_event_logging();
-            stop_recording();
-            is_rec.set(false);
-
-            // Save events to file (same as hotkey handler)
-            if let Some(ref path) = *current_events_path.read() {
-                let event_count = events.len();
-                let monitor = windows_capture::monitor::Monitor::primary().unwrap();
-                let event_log = zoom::EventLog {
-                    metadata: zoom::RecordingMetadata {
-                        width: monitor.width().unwrap_or(1920),
-                        height: monitor.height().unwrap_or(1080),
-                    },
-                    events,
-                };
-                if let Err(e) = zoom::save_event_log(&event_log, path) {
-                    eprintln!("Failed to save events: {e}");
+            if shared_state::take_replay_save() {
+                if replaying().is_some() {
+                    let path = RecorderConfig::from_config(&Config::load()).output_path;
+                    request_replay_save(path);
+                    status_message.set("Saving replay...".to_string());
+                    saved_at.set(None);
                 } else {
-                    println!("Events saved to: {:?}", path);
+                    let reason = if is_rec() {
+                        "Replay is off while recording"
+                    } else {
+                        "Instant replay is off"
+                    };
+                    status_message.set(reason.to_string());
+                    saved_at.set(Some(std::time::Instant::now()));
                 }
-
-                status_message.set(format!("✓ Saved {} events", event_count));
-                println!("Recording stopped, {} events captured", event_count);
             }
-            saved_at.set(Some(std::time::Instant::now()));
-        } else {
-            // Start recording and event logging
-            let monitor = windows_capture::monitor::Monitor::primary().unwrap();
-            let width = monitor.width().unwrap_or(1920);
-            let height = monitor.height().unwrap_or(1080);
-
-            start_event_logging(width, height);
-            let mut config = RecorderConfig::default();
-            config.width = width;
-            config.height = height;
-
-            current_events_path.set(Some(config.events_path.clone()));
-
-            match start_recording(config) {
-                Ok(_) => {
-                    is_rec.set(true);
-                    status_message.set("Recording...".to_string());
-                    println!("Recording started at {}x{}", width, height);
+
+            // Save the events covering a finished replay next to its video
+            match take_replay_saved() {
+                Some(Ok(saved)) => {
+                    let events_path = zoom::event_log_path(&saved.video_path);
+                    if let Some(event_log) = snapshot_event_log(saved.start_ms, saved.end_ms) {
+                        if let Err(e) = zoom::save_event_log(&event_log, &events_path) {
+                            eprintln!("Failed to save replay events: {e}");
+                        }
+                    }
+                    let secs = (saved.end_ms - saved.start_ms) / 1000;
+                    status_message.set(format!("✓ Saved {secs}s replay"));
+                    saved_at.set(Some(std::time::Instant::now()));
+                    println!("Replay saved to: {:?}", saved.video_path);
                 }
-                Err(e) => {
-                    stop_event_logging(); // Clean up
-                    current_events_path.set(None);
-                    status_message.set(format!("Error: {}", e));
-                    eprintln!("Failed to start recording: {e}");
+                Some(Err(e)) => {
+                    status_message.set(format!("Replay not saved: {e}"));
+                    saved_at.set(Some(std::time::Instant::now()));
+                    eprintln!("Failed to save replay: {e}");
                 }
+                None => {}
             }
         }
-    };
+    });
 
     rsx! {
         document::Link { rel: "icon", href: FAVICON }
@@ -238,7 +403,9 @@ fn App() -> Element {
             div { class: "flex items-center gap-3",
                 // Recording indicator dot
                 div {
-                    class: if is_rec() {
+                    class: if is_paused() {
+                        "w-2.5 h-2.5 rounded-full bg-amber-400"
+                    } else if is_rec() {
                         "w-2.5 h-2.5 rounded-full bg-red-500 animate-pulse shadow-lg shadow-red-500/50"
                     } else {
                         "w-2.5 h-2.5 rounded-full bg-gray-500"
@@ -247,25 +414,48 @@ fn App() -> Element {
 
                 // Status text - more compact
                 span {
-                    class: if is_rec() { "text-red-400 text-sm font-medium" } else { "text-gray-400 text-sm" },
+                    class: if is_paused() {
+                        "text-amber-400 text-sm font-medium"
+                    } else if is_rec() {
+                        "text-red-400 text-sm font-medium"
+                    } else {
+                        "text-gray-400 text-sm"
+                    },
                     "{status_message}"
                 }
 
+                // Pause/resume button, only while recording
+                if is_rec() {
+                    button {
+                        class: "px-3 py-1.5 bg-gray-700 hover:bg-gray-600 text-white rounded-lg text-sm font-medium transition-all",
+                        onclick: move |_| toggle_pause("UI"),
+                        if is_paused() { "▶ Resume" } else { "❚❚ Pause" }
+                    }
+                }
+
                 // Toggle button - sleeker
                 button {
                     class: if is_rec() {
                         "px-4 py-1.5 bg-red-600 hover:bg-red-500 text-white rounded-lg text-sm font-medium transition-all shadow-lg shadow-red-600/30"
+                    } else if countdown_until().is_some() {
+                        "px-4 py-1.5 bg-gray-600 hover:bg-gray-500 text-white rounded-lg text-sm font-medium transition-all"
                     } else {
                         "px-4 py-1.5 bg-emerald-600 hover:bg-emerald-500 text-white rounded-lg text-sm font-medium transition-all shadow-lg shadow-emerald-600/30"
                     },
-                    onclick: toggle_recording,
-                    if is_rec() { "■ Stop" } else { "● Record" }
+                    onclick: move |_| toggle_recording("UI"),
+                    if is_rec() {
+                        "■ Stop"
+                    } else if countdown_until().is_some() {
+                        "✕ Cancel"
+                    } else {
+                        "● Record"
+                    }
                 }
             }
         }
 
         // Recording border indicator (red glowing border when recording)
-        if is_rec() {
+        if is_rec() && !is_paused() {
             div {
                 class: "fixed inset-0 pointer-events-none z-40 border-4 border-red-500 animate-pulse",
                 style: "box-shadow: inset 0 0 20px rgba(239, 68, 68, 0.5);"
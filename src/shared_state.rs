@@ -1,6 +1,6 @@
 //! Shared state for cross-thread communication
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 /// Global flag set by hotkey thread when toggle is requested
 static HOTKEY_TOGGLE_REQUESTED: AtomicBool = AtomicBool::new(false);
@@ -15,3 +15,58 @@ pub fn request_hotkey_toggle() {
     HOTKEY_TOGGLE_REQUESTED.store(true, Ordering::SeqCst);
     println!("Hotkey toggle requested!");
 }
+
+/// Global flag set by hotkey thread when pause/resume is requested
+static HOTKEY_PAUSE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Check if a pause/resume was requested and clear the flag
+pub fn take_hotkey_pause() -> bool {
+    HOTKEY_PAUSE_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Request a pause/resume (called from hotkey listener thread)
+pub fn request_hotkey_pause() {
+    HOTKEY_PAUSE_REQUESTED.store(true, Ordering::SeqCst);
+    println!("Hotkey pause requested!");
+}
+
+/// Global flag set by hotkey thread when saving the instant replay is requested
+static REPLAY_SAVE_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Check if a replay save was requested and clear the flag
+pub fn take_replay_save() -> bool {
+    REPLAY_SAVE_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Request a replay save (called from hotkey listener thread)
+pub fn request_replay_save() {
+    REPLAY_SAVE_REQUESTED.store(true, Ordering::SeqCst);
+    println!("Replay save requested!");
+}
+
+/// Global flag set by hotkey thread when a chapter marker is requested
+static MARKER_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Check if a marker was requested and clear the flag
+pub fn take_marker_request() -> bool {
+    MARKER_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Request a chapter marker (called from hotkey listener thread)
+pub fn request_marker() {
+    MARKER_REQUESTED.store(true, Ordering::SeqCst);
+    println!("Marker requested!");
+}
+
+/// Seconds left in the pre-recording countdown (0 when no countdown is running)
+static COUNTDOWN_REMAINING: AtomicU32 = AtomicU32::new(0);
+
+/// Seconds left in the countdown, read by the overlay window
+pub fn countdown_remaining() -> u32 {
+    COUNTDOWN_REMAINING.load(Ordering::SeqCst)
+}
+
+/// Update the countdown; 0 ends it and closes the overlay
+pub fn set_countdown_remaining(secs: u32) {
+    COUNTDOWN_REMAINING.store(secs, Ordering::SeqCst);
+}
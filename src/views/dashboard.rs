@@ -19,13 +19,13 @@ pub struct RecordingEntry {
     pub events_path: Option<PathBuf>,
     pub size_bytes: u64,
     pub event_count: Option<usize>,
+    /// Percentage of dropped frames, set only when it is worth a warning
+    pub dropped_percent: Option<f32>,
 }
 
 /// Get the recordings directory
 fn get_recordings_dir() -> PathBuf {
-    dirs::video_dir()
-        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default())
-        .join("DemoRecorder")
+    crate::config::Config::load().output_folder
 }
 
 /// Scan for recordings in the output folder
@@ -44,43 +44,34 @@ fn scan_recordings() -> Vec<RecordingEntry> {
                     .to_string();
                 let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
 
-                // Check for matching events file
-                // Try both patterns: .events.json (replacing .mp4) and .mp4.events.json
-                let events_path_a = path.with_extension("events.json");
-                let events_path_b = PathBuf::from(format!("{}.events.json", path.display()));
-
-                let (events_path, events_exists) = if events_path_a.exists() {
-                    (events_path_a, true)
-                } else if events_path_b.exists() {
-                    (events_path_b, true)
-                } else {
-                    println!(
-                        "No events file found. Checked: {:?} and {:?}",
-                        events_path_a, events_path_b
-                    );
-                    (events_path_a, false)
-                };
-
-                // Count events if file exists
-                let event_count = if events_exists {
-                    std::fs::read_to_string(&events_path)
+                // Binary logs, or JSON ones from older versions
+                let events_path = crate::zoom::find_event_log(&path);
+                if events_path.is_none() {
+                    println!("No events file found for {:?}", path);
+                }
+
+                // Binary logs have the count in their header, so this stays cheap
+                let event_count = events_path.as_deref().and_then(|events_path| {
+                    crate::zoom::read_event_log_summary(events_path)
+                        .map_err(|e| eprintln!("Could not read {events_path:?}: {e}"))
                         .ok()
-                        .and_then(|s| serde_json::from_str::<Vec<serde_json::Value>>(&s).ok())
-                        .map(|v| v.len())
-                } else {
-                    None
-                };
+                        .map(|summary| summary.event_count as usize)
+                });
+
+                let dropped_percent = crate::capture::stats::CaptureStats::load(
+                    &crate::capture::stats::stats_path(&path),
+                )
+                .ok()
+                .filter(|stats| stats.has_significant_drops())
+                .map(|stats| stats.drop_percent());
 
                 entries.push(RecordingEntry {
                     filename,
                     path,
-                    events_path: if events_exists {
-                        Some(events_path)
-                    } else {
-                        None
-                    },
+                    events_path,
                     size_bytes,
                     event_count,
+                    dropped_percent,
                 });
             }
         }
@@ -109,6 +100,12 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
+/// Format a video time as `m:ss`
+fn format_timestamp(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
 /// Dashboard component
 #[component]
 pub fn Dashboard() -> Element {
@@ -230,6 +227,10 @@ fn RecordingCard(entry: RecordingEntry) -> Element {
                     .args(["/C", "start", "", &path.to_string_lossy()])
                     .spawn();
             }
+            #[cfg(target_os = "linux")]
+            {
+                let _ = std::process::Command::new("xdg-open").arg(&path).spawn();
+            }
         }
     };
 
@@ -245,6 +246,12 @@ fn RecordingCard(entry: RecordingEntry) -> Element {
                 }
             };
 
+            let settings = crate::config::Config::load();
+            if settings.zoom_mode == crate::config::ZoomMode::None {
+                status_msg.set("Zoom is off; pick a zoom mode in Settings".to_string());
+                return;
+            }
+
             status_msg.set("Loading events...".to_string());
 
             // Load event log from JSON file
@@ -274,15 +281,12 @@ fn RecordingCard(entry: RecordingEntry) -> Element {
                 .to_string();
 
             // Create config
+            // Size and frame rate come from the video itself; the clock offset
+            // from the log, set from the ⏱ panel
             let config = crate::zoom::PostProcessConfig {
                 input_path: input_path.to_string_lossy().to_string(),
                 output_path: output_path.to_string_lossy().to_string(),
-                width: 1920,
-                height: 1080,
-                fps: 30,
-                zoom_level: 1.5,
-                zoom_duration: 0.3,
-                hold_duration: 2.0,
+                ..crate::zoom::PostProcessConfig::from_config(&settings)
             };
 
             // Send work to the coroutine (non-blocking!)
@@ -294,15 +298,150 @@ fn RecordingCard(entry: RecordingEntry) -> Element {
         }
     };
 
+    // Show or hide the chapters from the recording's markers
+    let mut chapters = use_signal(|| None::<Vec<crate::zoom::Chapter>>);
+    let toggle_chapters = {
+        let events_path = entry.events_path.clone();
+        let video = entry.path.clone();
+        move |_| {
+            if chapters().is_some() {
+                chapters.set(None);
+                return;
+            }
+            let Some(events_path) = events_path.as_ref() else {
+                return;
+            };
+            match crate::zoom::load_event_log(events_path) {
+                Ok(log) => {
+                    // Without ffprobe the last event stands in for the end of the video
+                    let duration_ms = crate::capture::ffmpeg::probe_duration_ms(&video)
+                        .unwrap_or_else(|| {
+                            log.events
+                                .iter()
+                                .map(|e| e.timestamp_ms())
+                                .max()
+                                .unwrap_or(0)
+                                + 1
+                        });
+                    chapters.set(Some(log.chapters(duration_ms)));
+                }
+                Err(err) => status_msg.set(format!("Failed to load events: {}", err)),
+            }
+        }
+    };
+
+    // Show or hide the event timing offset; old logs may run early or late
+    let mut clock_offset = use_signal(|| None::<i64>);
+    let toggle_clock_offset = {
+        let events_path = entry.events_path.clone();
+        move |_| {
+            if clock_offset().is_some() {
+                clock_offset.set(None);
+                return;
+            }
+            let Some(events_path) = events_path.as_ref() else {
+                return;
+            };
+            match crate::zoom::load_event_log(events_path) {
+                Ok(log) => clock_offset.set(Some(log.metadata.clock_offset_ms)),
+                Err(err) => status_msg.set(format!("Failed to load events: {}", err)),
+            }
+        }
+    };
+    let set_clock_offset = {
+        let events_path = entry.events_path.clone();
+        move |e: Event<FormData>| {
+            let (Some(events_path), Ok(offset_ms)) = (events_path.as_ref(), e.value().parse())
+            else {
+                return;
+            };
+            match crate::zoom::set_event_clock_offset(events_path, offset_ms) {
+                Ok(()) => {
+                    clock_offset.set(Some(offset_ms));
+                    status_msg.set(format!("✓ Events shifted by {} ms", offset_ms));
+                }
+                Err(err) => status_msg.set(format!("Failed to save offset: {}", err)),
+            }
+        }
+    };
+
+    // Split the recording into parts at its markers, in the background
+    let split_recording = {
+        let path = entry.path.clone();
+        move |_| {
+            let path = path.clone();
+            processing.set(true);
+            status_msg.set("Splitting at markers...".to_string());
+            spawn(async move {
+                let result = tokio::task::spawn_blocking(move || {
+                    crate::capture::split_at_markers(&path).map_err(|e| e.to_string())
+                })
+                .await;
+                match result {
+                    Ok(Ok(parts)) => status_msg.set(format!(
+                        "✓ Split into {} parts (refresh to see them)",
+                        parts.len()
+                    )),
+                    Ok(Err(err)) => status_msg.set(format!("Error: {}", err)),
+                    Err(err) => status_msg.set(format!("Task error: {}", err)),
+                }
+                processing.set(false);
+            });
+        }
+    };
+
+    // Export events as JSON next to the recording, for other tools
+    let export_events = {
+        let events_path = entry.events_path.clone();
+        let json_path = entry.path.with_extension("events.json");
+        move |_| {
+            let Some(events_path) = events_path.as_ref() else {
+                return;
+            };
+            let result = crate::zoom::load_event_log(events_path)
+                .and_then(|log| crate::zoom::export_event_log_json(&log, &json_path));
+            match result {
+                Ok(()) => status_msg.set(format!("✓ Exported events to {}", json_path.display())),
+                Err(err) => status_msg.set(format!("Export failed: {}", err)),
+            }
+        }
+    };
+
+    // Scrub typed text from the events and their JSON export, if there is one
+    let redact_events = {
+        let events_path = entry.events_path.clone();
+        let json_path = entry.path.with_extension("events.json");
+        move |_| {
+            let Some(events_path) = events_path.as_ref() else {
+                return;
+            };
+            let policy = crate::zoom::RedactionPolicy::from_config(&crate::config::Config::load());
+            let mut result = crate::zoom::redact_event_log(events_path, &policy);
+            if json_path.exists() {
+                result = result.and_then(|changed| {
+                    Ok(changed + crate::zoom::redact_event_log(&json_path, &policy)?)
+                });
+            }
+            match result {
+                Ok(0) => status_msg.set("No typed text to redact".to_string()),
+                Ok(changed) => status_msg.set(format!("✓ Redacted {} events", changed)),
+                Err(err) => status_msg.set(format!("Redaction failed: {}", err)),
+            }
+        }
+    };
+
     // Delete recording
     let delete_recording = {
         let path = entry.path.clone();
         let events_path = entry.events_path.clone();
         move |_| {
             let _ = std::fs::remove_file(&path);
+            let _ = std::fs::remove_file(crate::capture::stats::stats_path(&path));
             if let Some(ref ep) = events_path {
                 let _ = std::fs::remove_file(ep);
             }
+            // A JSON export, if there is one
+            let _ = std::fs::remove_file(path.with_extension("events.json"));
         }
     };
 
@@ -323,6 +462,13 @@ fn RecordingCard(entry: RecordingEntry) -> Element {
                             } else {
                                 span { class: "text-gray-600", "No events" }
                             }
+                            if let Some(percent) = entry.dropped_percent {
+                                span {
+                                    class: "text-amber-400 bg-amber-500/10 px-1.5 py-0.5 rounded",
+                                    title: "Frames were dropped during capture; playback may stutter",
+                                    "⚠ {percent:.0}% frames dropped"
+                                }
+                            }
                         }
                     }
                 }
@@ -345,6 +491,30 @@ fn RecordingCard(entry: RecordingEntry) -> Element {
                             onclick: apply_zoom,
                             if processing() { "Processing..." } else { "🔍 Zoom" }
                         }
+                        button {
+                            class: "px-2.5 py-1.5 bg-gray-700/50 hover:bg-gray-600 rounded-lg text-sm transition-all",
+                            title: "Chapters",
+                            onclick: toggle_chapters,
+                            "📍"
+                        }
+                        button {
+                            class: "px-2.5 py-1.5 bg-gray-700/50 hover:bg-gray-600 rounded-lg text-sm transition-all",
+                            title: "Event timing",
+                            onclick: toggle_clock_offset,
+                            "⏱"
+                        }
+                        button {
+                            class: "px-2.5 py-1.5 bg-gray-700/50 hover:bg-gray-600 rounded-lg text-sm transition-all",
+                            title: "Export events as JSON",
+                            onclick: export_events,
+                            "{{ }}"
+                        }
+                        button {
+                            class: "px-2.5 py-1.5 bg-gray-700/50 hover:bg-gray-600 rounded-lg text-sm transition-all",
+                            title: "Redact typed text from events",
+                            onclick: redact_events,
+                            "🔒"
+                        }
                     }
                     button {
                         class: "px-2.5 py-1.5 bg-gray-700/50 hover:bg-red-600 rounded-lg text-sm transition-all",
@@ -354,6 +524,44 @@ fn RecordingCard(entry: RecordingEntry) -> Element {
                 }
             }
 
+            // Chapters from markers
+            if let Some(list) = chapters() {
+                div { class: "mt-3 px-3 py-2 bg-gray-900/40 rounded-lg text-sm",
+                    if list.is_empty() {
+                        div { class: "text-gray-500", "No markers. Press Ctrl+Shift+F12 while recording to add one." }
+                    } else {
+                        for chapter in list.iter() {
+                            div { class: "flex gap-3 py-0.5",
+                                span { class: "font-mono text-gray-500", "{format_timestamp(chapter.start_ms)}" }
+                                span { class: "text-gray-300", "{chapter.title}" }
+                            }
+                        }
+                        if list.len() > 1 {
+                            button {
+                                class: "mt-2 px-3 py-1 bg-gray-700/50 hover:bg-gray-600 rounded-lg text-xs transition-all",
+                                disabled: processing(),
+                                onclick: split_recording,
+                                "✂ Split at markers"
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Event timing offset
+            if let Some(offset_ms) = clock_offset() {
+                div { class: "mt-3 px-3 py-2 bg-gray-900/40 rounded-lg text-sm flex items-center gap-3",
+                    label { class: "text-gray-400", "Event offset (ms)" }
+                    input {
+                        class: "w-28 bg-gray-800 border border-gray-700 rounded-lg p-1.5 text-sm text-white",
+                        r#type: "number",
+                        value: "{offset_ms}",
+                        onchange: set_clock_offset,
+                    }
+                    span { class: "text-xs text-gray-500", "Positive if zooms come too early" }
+                }
+            }
+
             // Status message
             if !status_msg().is_empty() {
                 div { class: "mt-3 text-sm text-amber-400 bg-amber-500/10 px-3 py-1.5 rounded-lg", "{status_msg}" }
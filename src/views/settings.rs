@@ -1,13 +1,26 @@
 //! Settings view component
 
-use crate::config::{AudioMode, CaptureTarget, Config, OutputFormat};
+use crate::capture::{list_monitors, list_windows};
+use crate::config::{
+    AudioMode, CaptureTarget, Config, KeyLogging, OutputFormat, ZoomMode, ZoomTriggers,
+};
 use dioxus::prelude::*;
 
+/// Current Unix time in seconds, for the file name preview
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// Settings page component
 #[component]
 pub fn Settings() -> Element {
     let mut config = use_signal(Config::load);
     let mut save_status = use_signal(|| String::new());
+    let mut windows = use_signal(list_windows);
+    let monitors = use_signal(list_monitors);
 
     // Save handler
     let save_config = move |_| match config().save() {
@@ -25,17 +38,120 @@ pub fn Settings() -> Element {
                     label { class: "block text-sm font-medium mb-2", "Capture Source" }
                     select {
                         class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3",
-                        value: format!("{:?}", config().capture_target),
+                        value: config().capture_target.kind(),
                         onchange: move |e| {
                             let mut c = config();
                             c.capture_target = match e.value().as_str() {
                                 "ForegroundWindow" => CaptureTarget::ForegroundWindow,
+                                "Window" => CaptureTarget::Window { title: None, process: None },
+                                "Monitor" => CaptureTarget::Monitor(0),
+                                "Region" => {
+                                    // Start from the primary monitor's bounds so the fields aren't blank
+                                    let area = monitors()
+                                        .into_iter()
+                                        .find(|m| m.primary)
+                                        .map(|m| m.area)
+                                        .unwrap_or_default();
+                                    CaptureTarget::Region { x: area.x, y: area.y, w: area.width, h: area.height }
+                                }
                                 _ => CaptureTarget::PrimaryMonitor,
                             };
                             config.set(c);
                         },
                         option { value: "PrimaryMonitor", "Primary Monitor (Full Screen)" }
                         option { value: "ForegroundWindow", "Foreground Window" }
+                        option { value: "Window", "Specific Window" }
+                        option { value: "Monitor", "Specific Monitor" }
+                        option { value: "Region", "Screen Region" }
+                    }
+
+                    // Monitor picker, by position in the system's monitor list
+                    if let CaptureTarget::Monitor(index) = config().capture_target {
+                        select {
+                            class: "w-full mt-3 bg-gray-800 border border-gray-700 rounded-lg p-3 text-sm",
+                            onchange: move |e| {
+                                let mut c = config();
+                                c.capture_target = CaptureTarget::Monitor(e.value().parse().unwrap_or(0));
+                                config.set(c);
+                            },
+                            for (i, monitor) in monitors().into_iter().enumerate() {
+                                option {
+                                    value: "{i}",
+                                    selected: i == index,
+                                    "{i + 1}: {monitor.name} — {monitor.area.width}x{monitor.area.height} at ({monitor.area.x}, {monitor.area.y})"
+                                    if monitor.primary { " (primary)" }
+                                }
+                            }
+                        }
+                    }
+
+                    // Region in virtual-screen coordinates
+                    if let CaptureTarget::Region { x, y, w, h } = config().capture_target {
+                        div { class: "grid grid-cols-4 gap-2 mt-3",
+                            for (name, value, field) in [("X", x as i64, 0), ("Y", y as i64, 1), ("Width", w as i64, 2), ("Height", h as i64, 3)] {
+                                label { class: "text-xs text-gray-400",
+                                    "{name}"
+                                    input {
+                                        class: "w-full mt-1 bg-gray-800 border border-gray-700 rounded-lg p-2 text-sm text-white",
+                                        r#type: "number",
+                                        value: "{value}",
+                                        onchange: move |e| {
+                                            let Ok(v) = e.value().parse::<i64>() else {
+                                                return;
+                                            };
+                                            let mut c = config();
+                                            if let CaptureTarget::Region { x, y, w, h } = &mut c.capture_target {
+                                                match field {
+                                                    0 => *x = v as i32,
+                                                    1 => *y = v as i32,
+                                                    2 => *w = v.max(2) as u32,
+                                                    _ => *h = v.max(2) as u32,
+                                                }
+                                            }
+                                            config.set(c);
+                                        },
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Window picker, matched by title and process when recording starts
+                    if let CaptureTarget::Window { title, .. } = config().capture_target {
+                        div { class: "flex items-center gap-2 mt-3",
+                            select {
+                                class: "flex-1 bg-gray-800 border border-gray-700 rounded-lg p-3 text-sm",
+                                onchange: move |e| {
+                                    let Some(window) = e
+                                        .value()
+                                        .parse::<usize>()
+                                        .ok()
+                                        .and_then(|i| windows().get(i).cloned())
+                                    else {
+                                        return;
+                                    };
+                                    let mut c = config();
+                                    c.capture_target = CaptureTarget::Window {
+                                        title: Some(window.title),
+                                        process: Some(window.process).filter(|p| !p.is_empty()),
+                                    };
+                                    config.set(c);
+                                },
+                                option { value: "", selected: title.is_none(), "Select a window..." }
+                                for (i, window) in windows().into_iter().enumerate() {
+                                    option {
+                                        value: "{i}",
+                                        selected: title.as_deref() == Some(window.title.as_str()),
+                                        "{window.title} — {window.process}"
+                                    }
+                                }
+                            }
+                            button {
+                                class: "px-3 py-3 bg-gray-700 hover:bg-gray-600 rounded-lg text-sm transition",
+                                onclick: move |_| windows.set(list_windows()),
+                                "🔄"
+                            }
+                        }
                     }
                 }
 
@@ -100,6 +216,37 @@ pub fn Settings() -> Element {
                     }
                 }
 
+                // Countdown
+                div { class: "mb-6",
+                    label { class: "block text-sm font-medium mb-2", "Countdown" }
+                    div { class: "flex items-center gap-3",
+                        input {
+                            r#type: "checkbox",
+                            class: "w-5 h-5",
+                            checked: config().show_countdown,
+                            onchange: move |e| {
+                                let mut c = config();
+                                c.show_countdown = e.checked();
+                                config.set(c);
+                            },
+                        }
+                        select {
+                            class: "flex-1 bg-gray-800 border border-gray-700 rounded-lg p-3",
+                            disabled: !config().show_countdown,
+                            value: config().countdown_seconds.to_string(),
+                            onchange: move |e| {
+                                let mut c = config();
+                                c.countdown_seconds = e.value().parse().unwrap_or(3);
+                                config.set(c);
+                            },
+                            option { value: "3", "3 seconds" }
+                            option { value: "5", "5 seconds" }
+                            option { value: "10", "10 seconds" }
+                        }
+                    }
+                    p { class: "text-xs text-gray-500 mt-1", "Press the hotkey again during the countdown to cancel" }
+                }
+
                 // Hotkey display
                 div { class: "mb-6",
                     label { class: "block text-sm font-medium mb-2", "Hotkey" }
@@ -108,11 +255,182 @@ pub fn Settings() -> Element {
                     }
                 }
 
+                // Max duration
+                div { class: "mb-6",
+                    label { class: "block text-sm font-medium mb-2", "Max Recording Length" }
+                    select {
+                        class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3",
+                        value: config().max_duration_secs.to_string(),
+                        onchange: move |e| {
+                            let mut c = config();
+                            c.max_duration_secs = e.value().parse().unwrap_or(0);
+                            config.set(c);
+                        },
+                        option { value: "0", "No limit" }
+                        option { value: "300", "5 minutes" }
+                        option { value: "900", "15 minutes" }
+                        option { value: "1800", "30 minutes" }
+                        option { value: "3600", "1 hour" }
+                        option { value: "7200", "2 hours" }
+                    }
+                    p { class: "text-xs text-gray-500 mt-1", "You'll get a warning 30 seconds before the cutoff" }
+                }
+
+                // Instant replay
+                div { class: "mb-6",
+                    label { class: "block text-sm font-medium mb-2", "Instant Replay" }
+                    select {
+                        class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3",
+                        value: config().replay_buffer_secs.to_string(),
+                        onchange: move |e| {
+                            let mut c = config();
+                            c.replay_buffer_secs = e.value().parse().unwrap_or(0);
+                            config.set(c);
+                        },
+                        option { value: "0", "Off" }
+                        option { value: "15", "Last 15 seconds" }
+                        option { value: "30", "Last 30 seconds" }
+                        option { value: "60", "Last minute" }
+                        option { value: "120", "Last 2 minutes" }
+                    }
+                    p { class: "text-xs text-gray-500 mt-1", "Press Ctrl+Shift+F11 while not recording to save the buffer" }
+                }
+
+                // Input sampling rate
+                div { class: "mb-6",
+                    label { class: "block text-sm font-medium mb-2", "Input Sampling" }
+                    select {
+                        class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3",
+                        value: config().input_sample_hz.to_string(),
+                        onchange: move |e| {
+                            let mut c = config();
+                            c.input_sample_hz = e.value().parse().unwrap_or(120);
+                            config.set(c);
+                        },
+                        option { value: "60", "60 Hz" }
+                        option { value: "120", "120 Hz" }
+                        option { value: "240", "240 Hz" }
+                    }
+                    p { class: "text-xs text-gray-500 mt-1", "Higher rates track fast cursor movement more closely" }
+                }
+
+                // Zoom applied by the 🔍 button in the library
+                div { class: "mb-6",
+                    label { class: "block text-sm font-medium mb-2", "Zoom" }
+                    div { class: "flex gap-3",
+                        select {
+                            class: "flex-1 bg-gray-800 border border-gray-700 rounded-lg p-3",
+                            value: format!("{:?}", config().zoom_mode),
+                            onchange: move |e| {
+                                let mut c = config();
+                                c.zoom_mode = match e.value().as_str() {
+                                    "None" => ZoomMode::None,
+                                    "FollowCursor" => ZoomMode::FollowCursor,
+                                    _ => ZoomMode::ClickToZoom,
+                                };
+                                config.set(c);
+                            },
+                            option { value: "None", "No zoom" }
+                            option { value: "ClickToZoom", "Zoom in and out" }
+                            option { value: "FollowCursor", "Follow the cursor" }
+                        }
+                        select {
+                            class: "w-32 bg-gray-800 border border-gray-700 rounded-lg p-3",
+                            value: config().zoom_level.to_string(),
+                            onchange: move |e| {
+                                let mut c = config();
+                                c.zoom_level = e.value().parse().unwrap_or(1.5);
+                                config.set(c);
+                            },
+                            option { value: "1.25", "1.25×" }
+                            option { value: "1.5", "1.5×" }
+                            option { value: "2", "2×" }
+                            option { value: "2.5", "2.5×" }
+                        }
+                    }
+                    if matches!(config().zoom_mode, ZoomMode::ClickToZoom | ZoomMode::SmartAI) {
+                        select {
+                            class: "w-full mt-3 bg-gray-800 border border-gray-700 rounded-lg p-3",
+                            value: format!("{:?}", config().zoom_triggers),
+                            onchange: move |e| {
+                                let mut c = config();
+                                c.zoom_triggers = match e.value().as_str() {
+                                    "CursorSettle" => ZoomTriggers::CursorSettle,
+                                    "Both" => ZoomTriggers::Both,
+                                    _ => ZoomTriggers::Clicks,
+                                };
+                                config.set(c);
+                            },
+                            option { value: "Clicks", "Zoom on clicks" }
+                            option { value: "CursorSettle", "Zoom where the cursor comes to rest" }
+                            option { value: "Both", "Zoom on clicks and where the cursor rests" }
+                        }
+                    }
+                }
+
+                // Key logging and redaction
+                div { class: "mb-6",
+                    label { class: "block text-sm font-medium mb-2", "Key Logging" }
+                    select {
+                        class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3",
+                        value: format!("{:?}", config().key_logging),
+                        onchange: move |e| {
+                            let mut c = config();
+                            c.key_logging = match e.value().as_str() {
+                                "Off" => KeyLogging::Off,
+                                _ => KeyLogging::Categories,
+                            };
+                            config.set(c);
+                        },
+                        option { value: "Categories", "Shortcuts only (typed text hidden)" }
+                        option { value: "Off", "Off" }
+                    }
+                    label { class: "block text-sm text-gray-400 mt-3 mb-1", "Sensitive windows" }
+                    input {
+                        class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3 text-sm",
+                        value: config().sensitive_windows.join(", "),
+                        onchange: move |e| {
+                            let mut c = config();
+                            c.sensitive_windows = e
+                                .value()
+                                .split(',')
+                                .map(|word| word.trim().to_string())
+                                .filter(|word| !word.is_empty())
+                                .collect();
+                            config.set(c);
+                        },
+                    }
+                    p { class: "text-xs text-gray-500 mt-1", "No keys are logged while a window whose title or app contains one of these is in front" }
+                }
+
                 // Output folder
-                div { class: "mb-8",
+                div { class: "mb-6",
                     label { class: "block text-sm font-medium mb-2", "Output Folder" }
-                    div { class: "bg-gray-800 border border-gray-700 rounded-lg p-3 text-sm text-gray-400 truncate",
-                        "{config().output_folder.display()}"
+                    input {
+                        class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3 text-sm font-mono",
+                        value: "{config().output_folder.display()}",
+                        onchange: move |e| {
+                            let mut c = config();
+                            c.output_folder = std::path::PathBuf::from(e.value().trim());
+                            config.set(c);
+                        },
+                    }
+                }
+
+                // File name template
+                div { class: "mb-8",
+                    label { class: "block text-sm font-medium mb-2", "File Name" }
+                    input {
+                        class: "w-full bg-gray-800 border border-gray-700 rounded-lg p-3 text-sm font-mono",
+                        value: "{config().filename_template}",
+                        onchange: move |e| {
+                            let mut c = config();
+                            c.filename_template = e.value();
+                            config.set(c);
+                        },
+                    }
+                    p { class: "text-xs text-gray-500 mt-1",
+                        "Use {{timestamp}}, {{date}} and {{time}} (UTC). Next file: {config().recording_stem(now_secs())}.mp4"
                     }
                 }
 
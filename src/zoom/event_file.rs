@@ -0,0 +1,802 @@
+//! Compact binary event log files
+//!
+//! A file starts with a fixed-size header holding the event and pause counts
+//! and the log's duration, so the library can describe a recording without
+//! decoding it. The recording metadata follows as JSON, then the events and
+//! finally the pauses. Events store timestamps and positions as differences
+//! from the previous event, as variable-length integers, which keeps hour-long
+//! logs full of cursor samples small. Both ends stream: [`EventLogWriter`]
+//! appends events one at a time and [`EventLogReader`] yields them one at a
+//! time.
+//!
+//! Format version 2 (see [`EVENT_LOG_VERSION`]) is the first with a binary
+//! form; older logs only exist as JSON.
+
+use super::event_log::{
+    migrate_metadata, EventLog, Modifiers, MouseButton, PauseInterval, RecordedEvent,
+    RecordingMetadata, EVENT_LOG_VERSION,
+};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Extension of binary event logs, replacing the video's `.mp4`
+pub const EVENT_LOG_EXTENSION: &str = "events.bin";
+
+/// First bytes of a binary event log
+pub const EVENT_LOG_MAGIC: [u8; 4] = *b"DREV";
+
+/// Oldest format version written in binary
+const FIRST_BINARY_VERSION: u32 = 2;
+
+/// Size of the fixed header: magic, version, counts, duration, metadata length
+const HEADER_LEN: u64 = 32;
+
+/// Counts and duration of an event log, read from its header
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventLogSummary {
+    pub version: u32,
+    pub event_count: u64,
+    pub pause_count: u32,
+    /// Timestamp of the latest event
+    pub duration_ms: u64,
+}
+
+impl EventLogSummary {
+    fn of(log: &EventLog) -> Self {
+        Self {
+            version: log.version,
+            event_count: log.events.len() as u64,
+            pause_count: log.pauses.len() as u32,
+            duration_ms: log
+                .events
+                .iter()
+                .map(RecordedEvent::timestamp_ms)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Binary event log next to `video`
+pub fn event_log_path(video: &Path) -> PathBuf {
+    video.with_extension(EVENT_LOG_EXTENSION)
+}
+
+/// Event log of `video`, binary or JSON (from older versions or an export)
+pub fn find_event_log(video: &Path) -> Option<PathBuf> {
+    [
+        event_log_path(video),
+        video.with_extension("events.json"),
+        PathBuf::from(format!("{}.events.json", video.display())),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+}
+
+/// Whether `path` holds a binary event log
+pub fn is_binary_event_log(path: &Path) -> io::Result<bool> {
+    let mut magic = [0; 4];
+    let mut file = std::fs::File::open(path)?;
+    match file.read_exact(&mut magic) {
+        Ok(()) => Ok(magic == EVENT_LOG_MAGIC),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Counts and duration of the event log at `path`
+///
+/// Binary logs only need their header read; JSON logs are parsed in full.
+pub fn read_event_log_summary(path: &Path) -> Result<EventLogSummary, Box<dyn std::error::Error>> {
+    if is_binary_event_log(path)? {
+        let mut file = std::fs::File::open(path)?;
+        let (summary, _) = read_header(&mut file)?;
+        Ok(summary)
+    } else {
+        let log = super::event_log::load_event_log(&path.to_path_buf())?;
+        Ok(EventLogSummary::of(&log))
+    }
+}
+
+/// Write `log` to `path` in the binary format
+pub fn write_binary_event_log(log: &EventLog, path: &Path) -> io::Result<()> {
+    let file = io::BufWriter::new(std::fs::File::create(path)?);
+    let mut writer = EventLogWriter::new(file, &log.metadata)?;
+    for event in &log.events {
+        writer.write_event(event)?;
+    }
+    writer.finish(&log.pauses)?;
+    Ok(())
+}
+
+/// Read a whole binary event log
+pub fn read_binary_event_log(path: &Path) -> io::Result<EventLog> {
+    let file = io::BufReader::new(std::fs::File::open(path)?);
+    let mut reader = EventLogReader::new(file)?;
+    let events = reader.by_ref().collect::<io::Result<Vec<_>>>()?;
+    let (metadata, pauses) = reader.finish()?;
+    // Older binary versions only lack later event types and metadata, which the
+    // reader migrates, so they load as current
+    Ok(EventLog {
+        version: EVENT_LOG_VERSION,
+        metadata,
+        events,
+        pauses,
+    })
+}
+
+/// Streams events into a binary event log
+///
+/// The header's counts are filled in by [`EventLogWriter::finish`], so a log
+/// that was never finished reads as empty.
+pub struct EventLogWriter<W: Write + Seek> {
+    out: W,
+    summary: EventLogSummary,
+    last: Previous,
+}
+
+impl<W: Write + Seek> EventLogWriter<W> {
+    /// Start a log for a recording described by `metadata`
+    pub fn new(mut out: W, metadata: &RecordingMetadata) -> io::Result<Self> {
+        let metadata = serde_json::to_vec(metadata)?;
+        let summary = EventLogSummary {
+            version: EVENT_LOG_VERSION,
+            ..Default::default()
+        };
+        write_header(&mut out, &summary, metadata.len() as u32)?;
+        out.write_all(&metadata)?;
+        Ok(Self {
+            out,
+            summary,
+            last: Previous::default(),
+        })
+    }
+
+    pub fn write_event(&mut self, event: &RecordedEvent) -> io::Result<()> {
+        let mut buf = Vec::with_capacity(16);
+        encode_event(event, &mut self.last, &mut buf);
+        self.out.write_all(&buf)?;
+        self.summary.event_count += 1;
+        self.summary.duration_ms = self.summary.duration_ms.max(event.timestamp_ms());
+        Ok(())
+    }
+
+    /// Append the pauses and fill in the header
+    pub fn finish(mut self, pauses: &[PauseInterval]) -> io::Result<W> {
+        let mut buf = Vec::new();
+        for pause in pauses {
+            write_varint(&mut buf, pause.at_ms);
+            write_varint(&mut buf, pause.duration_ms);
+        }
+        self.out.write_all(&buf)?;
+        self.summary.pause_count = pauses.len() as u32;
+
+        // The metadata length is already right; rewrite the rest of the header
+        let end = self.out.stream_position()?;
+        self.out.seek(SeekFrom::Start(4))?;
+        write_counts(&mut self.out, &self.summary)?;
+        self.out.seek(SeekFrom::Start(end))?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Streams events out of a binary event log
+///
+/// Iterate to get the events, then call [`EventLogReader::finish`] for the
+/// metadata and pauses stored after them.
+pub struct EventLogReader<R: Read> {
+    input: R,
+    summary: EventLogSummary,
+    metadata: RecordingMetadata,
+    remaining: u64,
+    last: Previous,
+}
+
+impl<R: Read> EventLogReader<R> {
+    pub fn new(mut input: R) -> io::Result<Self> {
+        let (summary, metadata_len) = read_header(&mut input)?;
+        let mut metadata = vec![0; metadata_len as usize];
+        input.read_exact(&mut metadata)?;
+        let metadata = migrate_metadata(serde_json::from_slice(&metadata)?, summary.version)
+            .map_err(|e| invalid(&e.to_string()))?;
+        Ok(Self {
+            input,
+            summary,
+            metadata,
+            remaining: summary.event_count,
+            last: Previous::default(),
+        })
+    }
+
+    /// Skip any events left and read the pauses
+    pub fn finish(mut self) -> io::Result<(RecordingMetadata, Vec<PauseInterval>)> {
+        while self.remaining > 0 {
+            self.next_event()?;
+        }
+        let pauses = (0..self.summary.pause_count)
+            .map(|_| {
+                Ok(PauseInterval {
+                    at_ms: read_varint(&mut self.input)?,
+                    duration_ms: read_varint(&mut self.input)?,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        Ok((self.metadata, pauses))
+    }
+
+    fn next_event(&mut self) -> io::Result<RecordedEvent> {
+        self.remaining -= 1;
+        decode_event(&mut self.input, &mut self.last)
+    }
+}
+
+impl<R: Read> Iterator for EventLogReader<R> {
+    type Item = io::Result<RecordedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let event = self.next_event();
+        if event.is_err() {
+            // The rest of the stream can't be decoded without this event
+            self.remaining = 0;
+        }
+        Some(event)
+    }
+}
+
+fn write_header(
+    out: &mut impl Write,
+    summary: &EventLogSummary,
+    metadata_len: u32,
+) -> io::Result<()> {
+    out.write_all(&EVENT_LOG_MAGIC)?;
+    write_counts(out, summary)?;
+    out.write_all(&metadata_len.to_le_bytes())
+}
+
+/// The header after the magic, up to the metadata length
+fn write_counts(out: &mut impl Write, summary: &EventLogSummary) -> io::Result<()> {
+    out.write_all(&summary.version.to_le_bytes())?;
+    out.write_all(&summary.event_count.to_le_bytes())?;
+    out.write_all(&summary.pause_count.to_le_bytes())?;
+    out.write_all(&summary.duration_ms.to_le_bytes())
+}
+
+/// Read the header, returning the summary and the metadata length
+fn read_header(input: &mut impl Read) -> io::Result<(EventLogSummary, u32)> {
+    let mut header = [0; HEADER_LEN as usize];
+    input.read_exact(&mut header)?;
+    if header[..4] != EVENT_LOG_MAGIC {
+        return Err(invalid("Not a binary event log"));
+    }
+    let u32_at = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+    let u64_at = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+    let summary = EventLogSummary {
+        version: u32_at(4),
+        event_count: u64_at(8),
+        pause_count: u32_at(16),
+        duration_ms: u64_at(20),
+    };
+    if summary.version > EVENT_LOG_VERSION {
+        return Err(invalid(&format!(
+            "Event log format version {} is newer than this app supports \
+             ({EVENT_LOG_VERSION}); update DemoRecorder to open it",
+            summary.version
+        )));
+    }
+    if summary.version < FIRST_BINARY_VERSION {
+        return Err(invalid(&format!(
+            "Binary event logs start at version {FIRST_BINARY_VERSION}, found {}",
+            summary.version
+        )));
+    }
+    Ok((summary, u32_at(28)))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Timestamp and position of the previous event, which the next one is stored relative to
+#[derive(Default)]
+struct Previous {
+    timestamp_ms: u64,
+    x: i32,
+    y: i32,
+}
+
+const TAG_CLICK: u8 = 0;
+const TAG_MOUSE_UP: u8 = 1;
+const TAG_DOUBLE_CLICK: u8 = 2;
+const TAG_DRAG_START: u8 = 3;
+const TAG_DRAG_END: u8 = 4;
+const TAG_SCROLL: u8 = 5;
+const TAG_CURSOR_MOVE: u8 = 6;
+const TAG_KEY_DOWN: u8 = 7;
+const TAG_KEY_UP: u8 = 8;
+const TAG_ACTIVE_WINDOW: u8 = 9;
+const TAG_MARKER: u8 = 10;
+
+fn encode_event(event: &RecordedEvent, last: &mut Previous, out: &mut Vec<u8>) {
+    let tag = match event {
+        RecordedEvent::Click { .. } => TAG_CLICK,
+        RecordedEvent::MouseUp { .. } => TAG_MOUSE_UP,
+        RecordedEvent::DoubleClick { .. } => TAG_DOUBLE_CLICK,
+        RecordedEvent::DragStart { .. } => TAG_DRAG_START,
+        RecordedEvent::DragEnd { .. } => TAG_DRAG_END,
+        RecordedEvent::Scroll { .. } => TAG_SCROLL,
+        RecordedEvent::CursorMove { .. } => TAG_CURSOR_MOVE,
+        RecordedEvent::KeyDown { .. } => TAG_KEY_DOWN,
+        RecordedEvent::KeyUp { .. } => TAG_KEY_UP,
+        RecordedEvent::ActiveWindow { .. } => TAG_ACTIVE_WINDOW,
+        RecordedEvent::Marker { .. } => TAG_MARKER,
+    };
+    out.push(tag);
+    // Events are nearly always in order, but nothing guarantees it
+    let timestamp_ms = event.timestamp_ms();
+    write_signed(out, timestamp_ms as i64 - last.timestamp_ms as i64);
+    last.timestamp_ms = timestamp_ms;
+
+    if let Some((x, y)) = event.position() {
+        write_signed(out, x as i64 - last.x as i64);
+        write_signed(out, y as i64 - last.y as i64);
+        (last.x, last.y) = (x, y);
+    }
+
+    match event {
+        RecordedEvent::Click { button, .. }
+        | RecordedEvent::MouseUp { button, .. }
+        | RecordedEvent::DoubleClick { button, .. }
+        | RecordedEvent::DragStart { button, .. }
+        | RecordedEvent::DragEnd { button, .. } => out.push(button_code(*button)),
+        RecordedEvent::Scroll {
+            delta_x, delta_y, ..
+        } => {
+            out.extend_from_slice(&delta_x.to_le_bytes());
+            out.extend_from_slice(&delta_y.to_le_bytes());
+        }
+        RecordedEvent::CursorMove { .. } => {}
+        RecordedEvent::KeyDown { key, modifiers, .. }
+        | RecordedEvent::KeyUp { key, modifiers, .. } => {
+            out.push(modifier_bits(*modifiers));
+            write_string(out, key);
+        }
+        RecordedEvent::ActiveWindow {
+            title,
+            process,
+            x,
+            y,
+            width,
+            height,
+            ..
+        } => {
+            write_string(out, title);
+            write_string(out, process);
+            write_signed(out, *x as i64);
+            write_signed(out, *y as i64);
+            write_varint(out, *width as u64);
+            write_varint(out, *height as u64);
+        }
+        RecordedEvent::Marker { label, .. } => write_string(out, label),
+    }
+}
+
+fn decode_event(input: &mut impl Read, last: &mut Previous) -> io::Result<RecordedEvent> {
+    let tag = read_u8(input)?;
+    let timestamp_ms = (last.timestamp_ms as i64 + read_signed(input)?) as u64;
+    last.timestamp_ms = timestamp_ms;
+
+    let mut position = |input: &mut dyn Read| -> io::Result<(i32, i32)> {
+        last.x = (last.x as i64 + read_signed(input)?) as i32;
+        last.y = (last.y as i64 + read_signed(input)?) as i32;
+        Ok((last.x, last.y))
+    };
+
+    let event = match tag {
+        TAG_CLICK | TAG_MOUSE_UP | TAG_DOUBLE_CLICK | TAG_DRAG_START | TAG_DRAG_END => {
+            let (x, y) = position(input)?;
+            let button = button_from_code(read_u8(input)?)?;
+            match tag {
+                TAG_CLICK => RecordedEvent::Click {
+                    x,
+                    y,
+                    timestamp_ms,
+                    button,
+                },
+                TAG_MOUSE_UP => RecordedEvent::MouseUp {
+                    x,
+                    y,
+                    button,
+                    timestamp_ms,
+                },
+                TAG_DOUBLE_CLICK => RecordedEvent::DoubleClick {
+                    x,
+                    y,
+                    button,
+                    timestamp_ms,
+                },
+                TAG_DRAG_START => RecordedEvent::DragStart {
+                    x,
+                    y,
+                    button,
+                    timestamp_ms,
+                },
+                _ => RecordedEvent::DragEnd {
+                    x,
+                    y,
+                    button,
+                    timestamp_ms,
+                },
+            }
+        }
+        TAG_SCROLL => {
+            let (x, y) = position(input)?;
+            RecordedEvent::Scroll {
+                x,
+                y,
+                delta_x: read_f32(input)?,
+                delta_y: read_f32(input)?,
+                timestamp_ms,
+            }
+        }
+        TAG_CURSOR_MOVE => {
+            let (x, y) = position(input)?;
+            RecordedEvent::CursorMove { x, y, timestamp_ms }
+        }
+        TAG_KEY_DOWN | TAG_KEY_UP => {
+            let modifiers = modifiers_from_bits(read_u8(input)?);
+            let key = read_string(input)?;
+            if tag == TAG_KEY_DOWN {
+                RecordedEvent::KeyDown {
+                    key,
+                    modifiers,
+                    timestamp_ms,
+                }
+            } else {
+                RecordedEvent::KeyUp {
+                    key,
+                    modifiers,
+                    timestamp_ms,
+                }
+            }
+        }
+        TAG_ACTIVE_WINDOW => RecordedEvent::ActiveWindow {
+            title: read_string(input)?,
+            process: read_string(input)?,
+            x: read_signed(input)? as i32,
+            y: read_signed(input)? as i32,
+            width: read_varint(input)? as u32,
+            height: read_varint(input)? as u32,
+            timestamp_ms,
+        },
+        TAG_MARKER => RecordedEvent::Marker {
+            label: read_string(input)?,
+            timestamp_ms,
+        },
+        _ => return Err(invalid(&format!("Unknown event type {tag}"))),
+    };
+    Ok(event)
+}
+
+fn button_code(button: MouseButton) -> u8 {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Right => 1,
+        MouseButton::Middle => 2,
+        MouseButton::Back => 3,
+        MouseButton::Forward => 4,
+    }
+}
+
+fn button_from_code(code: u8) -> io::Result<MouseButton> {
+    Ok(match code {
+        0 => MouseButton::Left,
+        1 => MouseButton::Right,
+        2 => MouseButton::Middle,
+        3 => MouseButton::Back,
+        4 => MouseButton::Forward,
+        _ => return Err(invalid(&format!("Unknown mouse button {code}"))),
+    })
+}
+
+fn modifier_bits(modifiers: Modifiers) -> u8 {
+    modifiers.ctrl as u8
+        | ((modifiers.shift as u8) << 1)
+        | ((modifiers.alt as u8) << 2)
+        | ((modifiers.meta as u8) << 3)
+}
+
+fn modifiers_from_bits(bits: u8) -> Modifiers {
+    Modifiers {
+        ctrl: bits & 1 != 0,
+        shift: bits & 2 != 0,
+        alt: bits & 4 != 0,
+        meta: bits & 8 != 0,
+    }
+}
+
+/// LEB128: seven bits per byte, high bit set on all but the last
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Zigzag-encoded so small negative differences stay small
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn write_string(out: &mut Vec<u8>, text: &str) {
+    write_varint(out, text.len() as u64);
+    out.extend_from_slice(text.as_bytes());
+}
+
+fn read_varint(input: &mut (impl Read + ?Sized)) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_u8(input)?;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("Variable-length integer too long"))
+}
+
+fn read_signed(input: &mut (impl Read + ?Sized)) -> io::Result<i64> {
+    let value = read_varint(input)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+fn read_u8(input: &mut (impl Read + ?Sized)) -> io::Result<u8> {
+    let mut byte = [0];
+    input.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_f32(input: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_string(input: &mut impl Read) -> io::Result<String> {
+    let len = read_varint(input)? as usize;
+    let mut bytes = vec![0; len];
+    input.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| invalid("Text is not UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn metadata() -> RecordingMetadata {
+        RecordingMetadata {
+            width: 1920,
+            height: 1080,
+            origin_x: -1920,
+            origin_y: 0,
+            scale_factor: 1.5,
+            fps: 60,
+            app_version: "1.2.3".to_string(),
+            started_at_ms: 1_700_000_000_000,
+            capture_delay_ms: 85,
+            clock_offset_ms: -40,
+        }
+    }
+
+    /// One of every event, with positions and timestamps that go backwards too
+    fn all_events() -> Vec<RecordedEvent> {
+        let modifiers = Modifiers {
+            ctrl: true,
+            shift: false,
+            alt: true,
+            meta: true,
+        };
+        vec![
+            RecordedEvent::CursorMove {
+                x: 500,
+                y: 400,
+                timestamp_ms: 0,
+            },
+            RecordedEvent::Click {
+                x: 510,
+                y: 395,
+                timestamp_ms: 120,
+                button: MouseButton::Right,
+            },
+            RecordedEvent::MouseUp {
+                x: 510,
+                y: 395,
+                button: MouseButton::Right,
+                timestamp_ms: 180,
+            },
+            RecordedEvent::DoubleClick {
+                x: -30,
+                y: -5,
+                button: MouseButton::Left,
+                timestamp_ms: 150,
+            },
+            RecordedEvent::DragStart {
+                x: 40,
+                y: 60,
+                button: MouseButton::Middle,
+                timestamp_ms: 900,
+            },
+            RecordedEvent::DragEnd {
+                x: 1900,
+                y: 1070,
+                button: MouseButton::Back,
+                timestamp_ms: 1_500,
+            },
+            RecordedEvent::Scroll {
+                x: 0,
+                y: 0,
+                delta_x: -1.5,
+                delta_y: 3.0,
+                timestamp_ms: 1_400,
+            },
+            RecordedEvent::KeyDown {
+                key: "KeyS".to_string(),
+                modifiers,
+                timestamp_ms: 2_000,
+            },
+            RecordedEvent::KeyUp {
+                key: "KeyS".to_string(),
+                modifiers: Modifiers::default(),
+                timestamp_ms: 2_050,
+            },
+            RecordedEvent::ActiveWindow {
+                title: "Ünïcode — editor".to_string(),
+                process: "code".to_string(),
+                x: -100,
+                y: -20,
+                width: 800,
+                height: 600,
+                timestamp_ms: 2_100,
+            },
+            RecordedEvent::Marker {
+                label: String::new(),
+                timestamp_ms: 0,
+            },
+            RecordedEvent::Click {
+                x: 3,
+                y: 4,
+                timestamp_ms: 3_600_000,
+                button: MouseButton::Forward,
+            },
+        ]
+    }
+
+    fn write(events: &[RecordedEvent], pauses: &[PauseInterval]) -> Vec<u8> {
+        let mut writer = EventLogWriter::new(Cursor::new(Vec::new()), &metadata()).unwrap();
+        for event in events {
+            writer.write_event(event).unwrap();
+        }
+        writer.finish(pauses).unwrap().into_inner()
+    }
+
+    #[test]
+    fn round_trips_every_event() {
+        let events = all_events();
+        let pauses = [
+            PauseInterval {
+                at_ms: 1_000,
+                duration_ms: 5_000,
+            },
+            PauseInterval {
+                at_ms: 3_000,
+                duration_ms: 1,
+            },
+        ];
+        let bytes = write(&events, &pauses);
+
+        let mut reader = EventLogReader::new(Cursor::new(&bytes)).unwrap();
+        assert_eq!(
+            reader.summary,
+            EventLogSummary {
+                version: EVENT_LOG_VERSION,
+                event_count: events.len() as u64,
+                pause_count: 2,
+                duration_ms: 3_600_000,
+            }
+        );
+        let read = reader.by_ref().collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(read, events);
+
+        let (read_metadata, read_pauses) = reader.finish().unwrap();
+        assert_eq!(
+            serde_json::to_value(read_metadata).unwrap(),
+            serde_json::to_value(metadata()).unwrap()
+        );
+        let pause_pairs: Vec<_> = read_pauses
+            .iter()
+            .map(|p| (p.at_ms, p.duration_ms))
+            .collect();
+        assert_eq!(pause_pairs, [(1_000, 5_000), (3_000, 1)]);
+    }
+
+    #[test]
+    fn negative_deltas_round_trip() {
+        let events: Vec<_> = [(1_000, 0, 0), (10, -500, 2_000), (0, i32::MIN, i32::MAX)]
+            .into_iter()
+            .map(|(timestamp_ms, x, y)| RecordedEvent::CursorMove { x, y, timestamp_ms })
+            .collect();
+        let bytes = write(&events, &[]);
+
+        let reader = EventLogReader::new(Cursor::new(&bytes)).unwrap();
+        let read = reader.collect::<io::Result<Vec<_>>>().unwrap();
+        assert_eq!(read, events);
+    }
+
+    #[test]
+    fn signed_varints_stay_small() {
+        for (value, len) in [(0, 1), (-1, 1), (63, 1), (-64, 1), (64, 2), (i64::MIN, 10)] {
+            let mut buf = Vec::new();
+            write_signed(&mut buf, value);
+            assert_eq!(buf.len(), len, "{value}");
+            assert_eq!(read_signed(&mut buf.as_slice()).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn unfinished_log_reads_as_empty() {
+        // A crash before `finish` leaves the counts in the header at zero
+        let mut writer = EventLogWriter::new(Cursor::new(Vec::new()), &metadata()).unwrap();
+        for event in all_events() {
+            writer.write_event(&event).unwrap();
+        }
+        let bytes = writer.out.into_inner();
+
+        let mut reader = EventLogReader::new(Cursor::new(&bytes)).unwrap();
+        assert_eq!(reader.summary.event_count, 0);
+        assert!(reader.next().is_none());
+        let (read_metadata, pauses) = reader.finish().unwrap();
+        assert_eq!(read_metadata.fps, 60);
+        assert!(pauses.is_empty());
+    }
+
+    #[test]
+    fn truncated_file_is_an_error() {
+        let bytes = write(&all_events(), &[]);
+
+        // Cut inside the header, inside the metadata and inside the events
+        for len in [0, 10, HEADER_LEN as usize + 5] {
+            let err = EventLogReader::new(Cursor::new(&bytes[..len])).err();
+            assert_eq!(
+                err.map(|e| e.kind()),
+                Some(io::ErrorKind::UnexpectedEof),
+                "{len} bytes"
+            );
+        }
+        let reader = EventLogReader::new(Cursor::new(&bytes[..bytes.len() - 3])).unwrap();
+        assert!(reader.collect::<io::Result<Vec<_>>>().is_err());
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = write(&all_events(), &[]);
+        bytes[..4].copy_from_slice(b"RIFF");
+
+        let err = EventLogReader::new(Cursor::new(&bytes)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let mut bytes = write(&all_events(), &[]);
+        bytes[4..8].copy_from_slice(&(EVENT_LOG_VERSION + 1).to_le_bytes());
+
+        let err = EventLogReader::new(Cursor::new(&bytes)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("newer"));
+    }
+}
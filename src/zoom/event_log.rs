@@ -1,130 +1,1557 @@
 //! Event log for tracking mouse/keyboard events during recording
 //!
-//! Records clicks and cursor positions with timestamps for post-processing zoom
+//! Records mouse buttons, drags, scrolling, cursor positions, key presses,
+//! changes of the window in front and chapter markers with timestamps for
+//! post-processing zoom, shortcut overlays and chapters.
+//! Input is polled on a dedicated sampler thread at a fixed rate, which owns
+//! the log; the functions here hand it commands over a channel, so UI stalls
+//! never cost samples. Timestamps count from the capture clock (see
+//! [`crate::capture::clock`]), matching the video's frame timestamps.
+//! During a recording new events are appended to a journal every couple of
+//! seconds, so they survive a crash.
+//!
+//! Saved logs carry a format version. Older files are migrated on load, one
+//! version at a time, and files from a newer app are refused.
 
-use device_query::{DeviceQuery, DeviceState, MouseState};
+use super::event_file::{is_binary_event_log, read_binary_event_log, write_binary_event_log};
+use super::redaction::{RedactionPolicy, Redactor};
+use super::scroll::{start_scroll_listener, take_scroll, WHEEL_DELTA};
+use crate::capture::clock::capture_epoch;
+use crate::capture::{CaptureArea, ForegroundWatcher, WindowInfo};
+use device_query::{DeviceQuery, DeviceState, Keycode};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
+use serde_json::Value;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Format version of event logs written by this build
+///
+/// 0 was a bare list of events, 1 added the `EventLog` wrapper with the
+/// captured area, 2 added the version field and the rest of the metadata,
+/// 3 added `ActiveWindow` events, 4 added `Marker` events, 5 added the
+/// capture delay and clock offset to the metadata.
+pub const EVENT_LOG_VERSION: u32 = 5;
+
+/// File name of the event journal inside a recording's `.parts` folder
+pub const EVENT_JOURNAL_FILE: &str = "events.jsonl";
+
+/// How often new events are appended to the journal
+const JOURNAL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Two presses of a button closer than this in time and space are a double-click
+const DOUBLE_CLICK_MS: u64 = 500;
+const DOUBLE_CLICK_DISTANCE: i32 = 4;
+
+/// Moving this far (in pixels) with a button held turns the press into a drag
+const DRAG_THRESHOLD: i32 = 8;
+
+/// How often the window in front is checked; much slower than input sampling
+const WINDOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Requests to the sampler thread
+enum Command {
+    Start {
+        metadata: RecordingMetadata,
+        sample_hz: u32,
+        redaction: RedactionPolicy,
+    },
+    Pause,
+    Resume,
+    Marker(String),
+    SetRetention(Option<Duration>),
+    SetJournal(PathBuf, mpsc::Sender<std::io::Result<()>>),
+    Snapshot {
+        from_ms: u64,
+        to_ms: u64,
+        reply: mpsc::Sender<Option<EventLog>>,
+    },
+    Stop(mpsc::Sender<Option<EventLog>>),
+}
+
+/// Channel to the sampler thread, started on first use
+fn sampler() -> &'static mpsc::Sender<Command> {
+    static SAMPLER: OnceLock<mpsc::Sender<Command>> = OnceLock::new();
+    SAMPLER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || run_sampler(rx));
+        tx
+    })
+}
+
+fn send(command: Command) {
+    let _ = sampler().send(command);
+}
+
+/// Sampler thread: idles until logging starts, then samples at the configured rate
+fn run_sampler(commands: mpsc::Receiver<Command>) {
+    let mut state: Option<EventLoggerState> = None;
+    let mut next_tick = Instant::now();
+    loop {
+        if state.is_none() {
+            match commands.recv() {
+                Ok(command) => handle(&mut state, command),
+                Err(_) => return,
+            }
+            next_tick = Instant::now();
+        }
+        while let Ok(command) = commands.try_recv() {
+            handle(&mut state, command);
+        }
+
+        let Some(logger) = state.as_mut() else {
+            continue;
+        };
+        logger.sample();
+
+        // Fixed-rate schedule; after a stall skip the missed ticks instead of bursting
+        next_tick += logger.sample_interval;
+        let now = Instant::now();
+        if next_tick > now {
+            std::thread::sleep(next_tick - now);
+        } else {
+            next_tick = now;
+        }
+    }
+}
 
-/// Global event log
-static EVENTS_LOG: Mutex<Option<EventLoggerState>> = Mutex::new(None);
+/// Apply one command to the sampler's (possibly idle) logger
+fn handle(state: &mut Option<EventLoggerState>, command: Command) {
+    if let Some(state) = state {
+        state.align_to_capture_clock();
+    }
+    match command {
+        Command::Start {
+            metadata,
+            sample_hz,
+            redaction,
+        } => *state = Some(EventLoggerState::new(metadata, sample_hz, redaction)),
+        Command::Stop(reply) => {
+            let log = state.take().map(|state| {
+                println!(
+                    "Event logging stopped. {} events recorded, {} pauses.",
+                    state.events.len(),
+                    state.pauses.len()
+                );
+                EventLog {
+                    version: EVENT_LOG_VERSION,
+                    metadata: state.current_metadata(),
+                    events: state.events,
+                    pauses: state.pauses,
+                }
+            });
+            let _ = reply.send(log);
+        }
+        Command::SetJournal(path, reply) => {
+            let result = match state {
+                Some(state) => state.start_journal(&path),
+                None => Ok(()),
+            };
+            let _ = reply.send(result);
+        }
+        Command::Snapshot {
+            from_ms,
+            to_ms,
+            reply,
+        } => {
+            let log = state.as_ref().map(|state| state.snapshot(from_ms, to_ms));
+            let _ = reply.send(log);
+        }
+        Command::Pause => {
+            if let Some(state) = state {
+                state.pause();
+            }
+        }
+        Command::Resume => {
+            if let Some(state) = state {
+                state.resume();
+            }
+        }
+        Command::Marker(label) => {
+            if let Some(state) = state {
+                state.add_marker(label);
+            }
+        }
+        Command::SetRetention(retention) => {
+            if let Some(state) = state {
+                state.retention = retention;
+            }
+        }
+    }
+}
 
-/// State for the event logger
+/// State for the event logger, owned by the sampler thread
 struct EventLoggerState {
+    /// Metadata of the log being collected; its start time is filled in on collection
+    metadata: RecordingMetadata,
     events: Vec<RecordedEvent>,
+    /// Time base until the capture clock has its first frame
     start_time: Instant,
+    /// Whether events are on the capture clock yet
+    aligned: bool,
+    sample_interval: Duration,
     device_state: DeviceState,
-    last_mouse_state: MouseState,
-    last_sample_time: Instant,
+    /// Buttons held at the last update
+    presses: Vec<ButtonPress>,
+    /// Last recorded press, for double-click detection
+    last_click: Option<(MouseButton, i32, i32, u64)>,
+    /// Keys held at the last update, to detect presses and releases
+    last_keys: Vec<Keycode>,
+    /// Cursor position and time at the previous sample
+    last_cursor: Option<(i32, i32, u64)>,
+    /// Last cursor sample written to the log
+    logged_cursor: Option<(i32, i32, u64)>,
+    /// Key and window events pass through this before they are logged
+    redactor: Redactor,
+    windows: ForegroundWatcher,
+    /// Window in front at the last check, with its screen bounds
+    last_window: Option<(WindowInfo, CaptureArea)>,
+    last_window_check: Instant,
+    /// Screen position of the captured area, subtracted from cursor coordinates
+    origin: (i32, i32),
+    /// Size of the captured area
+    size: (u32, u32),
+    /// When the current pause began, if paused
+    paused_at: Option<Instant>,
+    /// Time spent paused so far, subtracted from timestamps
+    paused_total: Duration,
+    pauses: Vec<PauseInterval>,
+    /// Drop events older than this (instant replay keeps only a rolling window)
+    retention: Option<Duration>,
+    last_trim_time: Instant,
+    journal: Option<EventJournal>,
+}
+
+/// A mouse button being held
+struct ButtonPress {
+    button: MouseButton,
+    x: i32,
+    y: i32,
+    /// Whether the press was logged; releases of unlogged presses are ignored too
+    recorded: bool,
+    dragging: bool,
+}
+
+/// Append-only copy of the log on disk, one JSON entry per line
+struct EventJournal {
+    path: PathBuf,
+    /// Events and pauses already written
+    events_written: usize,
+    pauses_written: usize,
+    /// Whether the metadata changed since it was written
+    metadata_changed: bool,
+    last_write_time: Instant,
+}
+
+/// A line of the event journal
+#[derive(Serialize, Deserialize)]
+enum JournalEntry {
+    Metadata(RecordingMetadata),
+    Event(RecordedEvent),
+    Pause(PauseInterval),
+}
+
+impl EventLoggerState {
+    /// Log presses, releases, double-clicks and drags of the `held` buttons
+    ///
+    /// With `record` off (while paused) the held buttons are only tracked, so
+    /// a button held across resume isn't reported as a new press.
+    fn track_buttons(&mut self, held: &[MouseButton], (x, y): (i32, i32), record: bool) {
+        let timestamp_ms = self.video_time_ms();
+        // Presses on other monitors or outside the region can't be zoomed to
+        let in_area = (0..self.size.0 as i32).contains(&x) && (0..self.size.1 as i32).contains(&y);
+
+        // Releases
+        let (released, still_held): (Vec<_>, Vec<_>) = std::mem::take(&mut self.presses)
+            .into_iter()
+            .partition(|press| !held.contains(&press.button));
+        self.presses = still_held;
+        for press in released
+            .into_iter()
+            .filter(|press| press.recorded && record)
+        {
+            let button = press.button;
+            if press.dragging {
+                self.events.push(RecordedEvent::DragEnd {
+                    x,
+                    y,
+                    button,
+                    timestamp_ms,
+                });
+            }
+            self.events.push(RecordedEvent::MouseUp {
+                x,
+                y,
+                button,
+                timestamp_ms,
+            });
+        }
+
+        // Drags
+        for press in self.presses.iter_mut() {
+            let moved = (x - press.x).abs().max((y - press.y).abs());
+            if record && press.recorded && !press.dragging && moved >= DRAG_THRESHOLD {
+                press.dragging = true;
+                self.events.push(RecordedEvent::DragStart {
+                    x: press.x,
+                    y: press.y,
+                    button: press.button,
+                    timestamp_ms,
+                });
+            }
+        }
+
+        // New presses
+        for &button in held {
+            if self.presses.iter().any(|press| press.button == button) {
+                continue;
+            }
+            let recorded = record && in_area;
+            if recorded {
+                self.events.push(RecordedEvent::Click {
+                    x,
+                    y,
+                    button,
+                    timestamp_ms,
+                });
+                let is_double = self.last_click.is_some_and(|(b, cx, cy, t)| {
+                    b == button
+                        && timestamp_ms.saturating_sub(t) <= DOUBLE_CLICK_MS
+                        && (x - cx).abs().max((y - cy).abs()) <= DOUBLE_CLICK_DISTANCE
+                });
+                if is_double {
+                    self.events.push(RecordedEvent::DoubleClick {
+                        x,
+                        y,
+                        button,
+                        timestamp_ms,
+                    });
+                    self.last_click = None;
+                } else {
+                    self.last_click = Some((button, x, y, timestamp_ms));
+                }
+            }
+            self.presses.push(ButtonPress {
+                button,
+                x,
+                y,
+                recorded,
+                dragging: false,
+            });
+        }
+    }
+
+    /// Append events and pauses added since the last write to the journal
+    ///
+    /// Nothing is written until the events are on the capture clock, since
+    /// until then their timestamps may still change.
+    fn write_journal(&mut self) -> std::io::Result<()> {
+        if !self.aligned {
+            return Ok(());
+        }
+        let metadata = self.current_metadata();
+        let Some(journal) = self.journal.as_mut() else {
+            return Ok(());
+        };
+        journal.last_write_time = Instant::now();
+
+        let mut lines = String::new();
+        // Recovery keeps the last metadata entry
+        let entries = journal
+            .metadata_changed
+            .then_some(JournalEntry::Metadata(metadata))
+            .into_iter()
+            .chain(
+                self.events[journal.events_written..]
+                    .iter()
+                    .cloned()
+                    .map(JournalEntry::Event),
+            )
+            .chain(
+                self.pauses[journal.pauses_written..]
+                    .iter()
+                    .copied()
+                    .map(JournalEntry::Pause),
+            );
+        for entry in entries {
+            lines.push_str(&serde_json::to_string(&entry)?);
+            lines.push('\n');
+        }
+        // Don't create the file if the recording's folder is already gone
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&journal.path)?;
+        file.write_all(lines.as_bytes())?;
+        journal.events_written = self.events.len();
+        journal.pauses_written = self.pauses.len();
+        journal.metadata_changed = false;
+        Ok(())
+    }
+
+    fn new(metadata: RecordingMetadata, sample_hz: u32, redaction: RedactionPolicy) -> Self {
+        let origin = (metadata.origin_x, metadata.origin_y);
+        let size = (metadata.width, metadata.height);
+        start_scroll_listener();
+        // Scrolling from before logging started
+        take_scroll();
+        println!(
+            "Event logging started for {}x{} at ({}, {}), sampling at {} Hz",
+            size.0, size.1, origin.0, origin.1, sample_hz
+        );
+
+        Self {
+            metadata,
+            events: Vec::new(),
+            start_time: Instant::now(),
+            aligned: false,
+            sample_interval: Duration::from_secs_f64(1.0 / sample_hz.clamp(30, 1000) as f64),
+            device_state: DeviceState::new(),
+            presses: Vec::new(),
+            last_click: None,
+            last_keys: Vec::new(),
+            last_cursor: None,
+            logged_cursor: None,
+            redactor: Redactor::new(redaction),
+            windows: ForegroundWatcher::connect(),
+            last_window: None,
+            last_window_check: Instant::now(),
+            origin,
+            size,
+            paused_at: None,
+            paused_total: Duration::ZERO,
+            pauses: Vec::new(),
+            retention: None,
+            last_trim_time: Instant::now(),
+            journal: None,
+        }
+    }
+
+    /// Milliseconds of recorded (unpaused) time, matching the video timeline
+    fn video_time_ms(&self) -> u64 {
+        let epoch = capture_epoch().unwrap_or(self.start_time);
+        Instant::now()
+            .saturating_duration_since(epoch)
+            .saturating_sub(self.paused_total)
+            .as_millis() as u64
+    }
+
+    /// Metadata of the log as collected so far
+    fn current_metadata(&self) -> RecordingMetadata {
+        RecordingMetadata {
+            started_at_ms: self.started_at_ms(),
+            ..self.metadata.clone()
+        }
+    }
+
+    /// Wall-clock time of video time zero, in milliseconds since the Unix epoch
+    fn started_at_ms(&self) -> u64 {
+        let epoch = capture_epoch().unwrap_or(self.start_time);
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .saturating_sub(epoch.elapsed())
+            .as_millis() as u64
+    }
+
+    /// Move events logged before the first frame onto the capture clock
+    ///
+    /// Until the capture clock has its first frame, timestamps count from
+    /// when logging started. Once it has, those events are shifted by the
+    /// difference so the whole log shares the video's timeline, and events
+    /// from before the video starts are dropped: nothing of them is on screen.
+    /// The difference is kept as [`RecordingMetadata::capture_delay_ms`].
+    fn align_to_capture_clock(&mut self) {
+        if self.aligned {
+            return;
+        }
+        let Some(epoch) = capture_epoch() else {
+            return;
+        };
+        self.aligned = true;
+        // How long after logging started video time zero is (negative if before)
+        let delay_ms = if epoch >= self.start_time {
+            epoch.duration_since(self.start_time).as_millis() as i64
+        } else {
+            -(self.start_time.duration_since(epoch).as_millis() as i64)
+        };
+        self.metadata.capture_delay_ms = delay_ms;
+        // Video time of a timestamp from before alignment, `None` if before the video
+        let shift = |t: u64| u64::try_from(t as i64 - delay_ms).ok();
+
+        let (kept, dropped): (Vec<_>, Vec<_>) = std::mem::take(&mut self.events)
+            .into_iter()
+            .partition(|event| shift(event.timestamp_ms()).is_some());
+        self.events = kept;
+        for event in &mut self.events {
+            let t = event.timestamp_ms_mut();
+            *t = shift(*t).unwrap_or_default();
+        }
+        self.pauses.retain(|pause| shift(pause.at_ms).is_some());
+        for pause in &mut self.pauses {
+            pause.at_ms = shift(pause.at_ms).unwrap_or_default();
+        }
+        self.last_click = self
+            .last_click
+            .and_then(|(button, x, y, t)| Some((button, x, y, shift(t)?)));
+        self.last_cursor = self
+            .last_cursor
+            .and_then(|(x, y, t)| Some((x, y, shift(t)?)));
+        // Without a logged position the next sample logs where the cursor is
+        self.logged_cursor = self
+            .logged_cursor
+            .and_then(|(x, y, t)| Some((x, y, shift(t)?)));
+
+        // Releases of dropped presses aren't logged either
+        for press in &mut self.presses {
+            let clicked = |events: &[RecordedEvent]| {
+                events.iter().any(|event| {
+                    matches!(event, RecordedEvent::Click { button, .. } if *button == press.button)
+                })
+            };
+            if clicked(&dropped) && !clicked(&self.events) {
+                press.recorded = false;
+            }
+        }
+        // Log the window in front again if its event was dropped
+        if dropped
+            .iter()
+            .any(|event| matches!(event, RecordedEvent::ActiveWindow { .. }))
+        {
+            self.last_window = None;
+        }
+        if let Some(journal) = self.journal.as_mut() {
+            journal.metadata_changed = true;
+        }
+
+        println!(
+            "First frame captured {delay_ms} ms after event logging started, {} earlier events dropped",
+            dropped.len()
+        );
+    }
+
+    /// Poll the mouse and keyboard once and log what changed
+    fn sample(&mut self) {
+        self.align_to_capture_clock();
+        let mouse = self.device_state.get_mouse();
+        let keys = self.device_state.get_keys();
+        let held = MouseButton::held(&mouse.button_pressed);
+        let (x, y) = (
+            mouse.coords.0 - self.origin.0,
+            mouse.coords.1 - self.origin.1,
+        );
+        let (scroll_x, scroll_y) = take_scroll();
+
+        // Nothing is recorded while paused, but keep the button and key state
+        // so a click or key held across resume isn't reported as a new one
+        if self.paused_at.is_some() {
+            self.track_buttons(&held, (x, y), false);
+            self.last_keys = keys;
+            return;
+        }
+
+        let timestamp_ms = self.video_time_ms();
+        self.track_buttons(&held, (x, y), true);
+
+        let in_area = (0..self.size.0 as i32).contains(&x) && (0..self.size.1 as i32).contains(&y);
+        if (scroll_x, scroll_y) != (0, 0) && in_area {
+            self.events.push(RecordedEvent::Scroll {
+                x,
+                y,
+                delta_x: scroll_x as f32 / WHEEL_DELTA as f32,
+                delta_y: scroll_y as f32 / WHEEL_DELTA as f32,
+                timestamp_ms,
+            });
+        }
+
+        // Key presses and releases since the last sample. A new press checks
+        // the window in front first, so typing right after switching to a
+        // password prompt is redacted too.
+        if keys.iter().any(|k| !self.last_keys.contains(k)) {
+            self.last_window_check = Instant::now();
+            self.check_window(timestamp_ms);
+        }
+        let modifiers = Modifiers::from_keys(&keys);
+        let pressed = keys
+            .iter()
+            .filter(|k| !self.last_keys.contains(k))
+            .map(|key| RecordedEvent::KeyDown {
+                key: key_name(key),
+                modifiers,
+                timestamp_ms,
+            });
+        let released = self
+            .last_keys
+            .iter()
+            .filter(|k| !keys.contains(k))
+            .map(|key| RecordedEvent::KeyUp {
+                key: key_name(key),
+                modifiers,
+                timestamp_ms,
+            });
+        let key_events: Vec<_> = pressed.chain(released).collect();
+        for event in key_events {
+            if let Some(event) = self.redactor.redact(event) {
+                self.events.push(event);
+            }
+        }
+        self.last_keys = keys;
+
+        // Log the cursor only when it moves. When it starts moving again, the
+        // last still sample goes in first so interpolation doesn't drift
+        // across the whole time it stood still.
+        let cursor = (x, y, timestamp_ms);
+        let moved = self
+            .logged_cursor
+            .is_none_or(|(lx, ly, _)| (lx, ly) != (x, y));
+        if moved {
+            if let Some(still @ (sx, sy, st)) = self.last_cursor {
+                if self.logged_cursor != Some(still) {
+                    self.events.push(RecordedEvent::CursorMove {
+                        x: sx,
+                        y: sy,
+                        timestamp_ms: st,
+                    });
+                }
+            }
+            self.events
+                .push(RecordedEvent::CursorMove { x, y, timestamp_ms });
+            self.logged_cursor = Some(cursor);
+        }
+        self.last_cursor = Some(cursor);
+
+        if self.last_window_check.elapsed() >= WINDOW_POLL_INTERVAL {
+            self.last_window_check = Instant::now();
+            self.check_window(timestamp_ms);
+        }
+
+        // Trim the rolling window about once a second rather than every sample
+        if let Some(retention) = self.retention {
+            if self.last_trim_time.elapsed() >= Duration::from_secs(1) {
+                let cutoff = timestamp_ms.saturating_sub(retention.as_millis() as u64);
+                let before = self.events.len();
+                self.events.retain(|e| e.timestamp_ms() >= cutoff);
+                if let Some(journal) = self.journal.as_mut() {
+                    journal.events_written = journal
+                        .events_written
+                        .saturating_sub(before - self.events.len());
+                }
+                self.last_trim_time = Instant::now();
+            }
+        }
+
+        if self
+            .journal
+            .as_ref()
+            .is_some_and(|j| j.last_write_time.elapsed() >= JOURNAL_INTERVAL)
+        {
+            if let Err(e) = self.write_journal() {
+                eprintln!("Failed to write event journal: {e}");
+            }
+        }
+    }
+
+    /// Log the window in front if it changed: another window, a new title or new bounds
+    fn check_window(&mut self, timestamp_ms: u64) {
+        let current = self.windows.current();
+        if current == self.last_window {
+            return;
+        }
+        // Keys typed into a window that can't be identified might be a password
+        if current.is_none() {
+            self.redactor.window_unknown();
+        }
+        if let Some((info, bounds)) = &current {
+            let event = RecordedEvent::ActiveWindow {
+                title: info.title.clone(),
+                process: info.process.clone(),
+                x: bounds.x - self.origin.0,
+                y: bounds.y - self.origin.1,
+                width: bounds.width,
+                height: bounds.height,
+                timestamp_ms,
+            };
+            self.events.extend(self.redactor.redact(event));
+        }
+        self.last_window = current;
+    }
+
+    /// Create the journal at `path`, starting with the log's metadata
+    fn start_journal(&mut self, path: &Path) -> std::io::Result<()> {
+        let metadata = self.current_metadata();
+        let mut header = serde_json::to_string(&JournalEntry::Metadata(metadata))?;
+        header.push('\n');
+        std::fs::write(path, header)?;
+        self.journal = Some(EventJournal {
+            path: path.to_path_buf(),
+            events_written: 0,
+            pauses_written: 0,
+            metadata_changed: false,
+            last_write_time: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Log of the events between `from_ms` and `to_ms`, shifted so `from_ms` becomes zero
+    fn snapshot(&self, from_ms: u64, to_ms: u64) -> EventLog {
+        let events = self
+            .events
+            .iter()
+            .filter(|e| (from_ms..=to_ms).contains(&e.timestamp_ms()))
+            .cloned()
+            .map(|mut e| {
+                *e.timestamp_ms_mut() -= from_ms;
+                e
+            })
+            .collect();
+        EventLog {
+            version: EVENT_LOG_VERSION,
+            metadata: RecordingMetadata {
+                started_at_ms: self.started_at_ms() + from_ms,
+                ..self.metadata.clone()
+            },
+            events,
+            pauses: Vec::new(),
+        }
+    }
+
+    /// Mark the current moment; markers while paused would point at a cut
+    fn add_marker(&mut self, label: String) {
+        if self.paused_at.is_none() {
+            let timestamp_ms = self.video_time_ms();
+            self.events.push(RecordedEvent::Marker {
+                label,
+                timestamp_ms,
+            });
+        }
+    }
+
+    fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            let paused_for = paused_at.elapsed();
+            // Video time stood still during the pause, so this is where the cut is
+            let at_ms = self
+                .video_time_ms()
+                .saturating_sub(paused_for.as_millis() as u64);
+            self.paused_total += paused_for;
+            self.pauses.push(PauseInterval {
+                at_ms,
+                duration_ms: paused_for.as_millis() as u64,
+            });
+        }
+    }
 }
 
 /// A recorded event during capture
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum RecordedEvent {
-    /// Mouse click at position
-    Click { x: i32, y: i32, timestamp_ms: u64 },
+    /// Mouse button pressed at position
+    Click {
+        x: i32,
+        y: i32,
+        timestamp_ms: u64,
+        /// Logs from before buttons were told apart only have left clicks
+        #[serde(default)]
+        button: MouseButton,
+    },
+    /// Mouse button released (only for presses that were logged)
+    MouseUp {
+        x: i32,
+        y: i32,
+        button: MouseButton,
+        timestamp_ms: u64,
+    },
+    /// Second press of a double-click, logged right after its `Click`
+    DoubleClick {
+        x: i32,
+        y: i32,
+        button: MouseButton,
+        timestamp_ms: u64,
+    },
+    /// Cursor moved far enough with a button held; `x`/`y` is where the press was
+    DragStart {
+        x: i32,
+        y: i32,
+        button: MouseButton,
+        timestamp_ms: u64,
+    },
+    /// Button of a drag released at position, logged right before its `MouseUp`
+    DragEnd {
+        x: i32,
+        y: i32,
+        button: MouseButton,
+        timestamp_ms: u64,
+    },
+    /// Mouse wheel movement in notches; positive scrolls right/down
+    Scroll {
+        x: i32,
+        y: i32,
+        delta_x: f32,
+        delta_y: f32,
+        timestamp_ms: u64,
+    },
     /// Cursor position sample
     CursorMove { x: i32, y: i32, timestamp_ms: u64 },
+    /// Key pressed, with the modifiers held at that moment (including itself)
+    KeyDown {
+        key: String,
+        modifiers: Modifiers,
+        timestamp_ms: u64,
+    },
+    /// Key released, with the modifiers still held afterwards
+    KeyUp {
+        key: String,
+        modifiers: Modifiers,
+        timestamp_ms: u64,
+    },
+    /// Another window came to the front, or the one in front changed title or
+    /// bounds; `x`/`y` is its top-left corner relative to the captured area
+    ActiveWindow {
+        title: String,
+        process: String,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        timestamp_ms: u64,
+    },
+    /// Chapter marker added with the marker hotkey
+    Marker { label: String, timestamp_ms: u64 },
+}
+
+impl RecordedEvent {
+    /// Video time of the event in milliseconds
+    pub fn timestamp_ms(&self) -> u64 {
+        match self {
+            RecordedEvent::Click { timestamp_ms, .. }
+            | RecordedEvent::MouseUp { timestamp_ms, .. }
+            | RecordedEvent::DoubleClick { timestamp_ms, .. }
+            | RecordedEvent::DragStart { timestamp_ms, .. }
+            | RecordedEvent::DragEnd { timestamp_ms, .. }
+            | RecordedEvent::Scroll { timestamp_ms, .. }
+            | RecordedEvent::CursorMove { timestamp_ms, .. }
+            | RecordedEvent::KeyDown { timestamp_ms, .. }
+            | RecordedEvent::KeyUp { timestamp_ms, .. }
+            | RecordedEvent::ActiveWindow { timestamp_ms, .. }
+            | RecordedEvent::Marker { timestamp_ms, .. } => *timestamp_ms,
+        }
+    }
+
+    fn timestamp_ms_mut(&mut self) -> &mut u64 {
+        match self {
+            RecordedEvent::Click { timestamp_ms, .. }
+            | RecordedEvent::MouseUp { timestamp_ms, .. }
+            | RecordedEvent::DoubleClick { timestamp_ms, .. }
+            | RecordedEvent::DragStart { timestamp_ms, .. }
+            | RecordedEvent::DragEnd { timestamp_ms, .. }
+            | RecordedEvent::Scroll { timestamp_ms, .. }
+            | RecordedEvent::CursorMove { timestamp_ms, .. }
+            | RecordedEvent::KeyDown { timestamp_ms, .. }
+            | RecordedEvent::KeyUp { timestamp_ms, .. }
+            | RecordedEvent::ActiveWindow { timestamp_ms, .. }
+            | RecordedEvent::Marker { timestamp_ms, .. } => timestamp_ms,
+        }
+    }
+
+    /// Cursor position carried by the event, if it has one
+    pub fn position(&self) -> Option<(i32, i32)> {
+        match self {
+            RecordedEvent::Click { x, y, .. }
+            | RecordedEvent::MouseUp { x, y, .. }
+            | RecordedEvent::DoubleClick { x, y, .. }
+            | RecordedEvent::DragStart { x, y, .. }
+            | RecordedEvent::DragEnd { x, y, .. }
+            | RecordedEvent::Scroll { x, y, .. }
+            | RecordedEvent::CursorMove { x, y, .. } => Some((*x, *y)),
+            RecordedEvent::KeyDown { .. }
+            | RecordedEvent::KeyUp { .. }
+            | RecordedEvent::ActiveWindow { .. }
+            | RecordedEvent::Marker { .. } => None,
+        }
+    }
+}
+
+/// A mouse button
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MouseButton {
+    #[default]
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+impl MouseButton {
+    /// Buttons held in a `device_query` button state
+    ///
+    /// The indices follow the platform: Windows reports left, right, middle,
+    /// back, forward; X11 reports buttons 1-5, where 2 is middle, 3 is right
+    /// and 4/5 are the wheel (see [`super::scroll`]).
+    fn held(pressed: &[bool]) -> Vec<MouseButton> {
+        #[cfg(windows)]
+        const BY_INDEX: [Option<MouseButton>; 6] = [
+            None,
+            Some(MouseButton::Left),
+            Some(MouseButton::Right),
+            Some(MouseButton::Middle),
+            Some(MouseButton::Back),
+            Some(MouseButton::Forward),
+        ];
+        #[cfg(not(windows))]
+        const BY_INDEX: [Option<MouseButton>; 6] = [
+            None,
+            Some(MouseButton::Left),
+            Some(MouseButton::Middle),
+            Some(MouseButton::Right),
+            None,
+            None,
+        ];
+        pressed
+            .iter()
+            .zip(BY_INDEX)
+            .filter_map(|(&down, button)| button.filter(|_| down))
+            .collect()
+    }
+}
+
+/// Modifier keys held during a key event
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    /// Windows/Command/Super key
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// Modifiers among the held `keys`, either side of the keyboard
+    fn from_keys(keys: &[Keycode]) -> Self {
+        // Matched by name: the left/right and platform variants differ
+        // between device_query versions
+        let mut modifiers = Modifiers::default();
+        for key in keys {
+            let name = key_name(key);
+            modifiers.ctrl |= name.ends_with("Control");
+            modifiers.shift |= name.ends_with("Shift");
+            modifiers.alt |= name.ends_with("Alt") || name.ends_with("Option");
+            modifiers.meta |= name.ends_with("Meta") || name.ends_with("Command");
+        }
+        modifiers
+    }
+}
+
+/// Name a key is stored under, e.g. `"A"`, `"F5"` or `"LControl"`
+fn key_name(key: &Keycode) -> String {
+    format!("{key:?}")
+}
+
+/// A stretch of time the recording was paused
+///
+/// Paused time is cut from the video, so `at_ms` is the video time of the cut
+/// and `duration_ms` is how long the pause lasted in real time.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PauseInterval {
+    pub at_ms: u64,
+    pub duration_ms: u64,
 }
 
 /// Metadata for the recording session
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RecordingMetadata {
+    /// Size of the captured area; 0 if unknown (logs from before it was recorded)
     pub width: u32,
     pub height: u32,
+    /// Screen position of the captured area; event coordinates are relative to it
+    pub origin_x: i32,
+    pub origin_y: i32,
+    /// Display scale of the captured monitor, 1.0 at 96 DPI
+    pub scale_factor: f64,
+    /// Capture frame rate; 0 if unknown
+    pub fps: u32,
+    /// Version of the app that recorded the log; empty if unknown
+    pub app_version: String,
+    /// Wall-clock time of video time zero, in milliseconds since the Unix epoch; 0 if unknown
+    pub started_at_ms: u64,
+    /// How long after event logging started the first frame was captured,
+    /// in milliseconds (negative if before); 0 if unknown
+    ///
+    /// Events logged before the first frame aren't in the log.
+    pub capture_delay_ms: i64,
+    /// Milliseconds to add to event timestamps to get video time
+    ///
+    /// 0 when events were timed against the capture clock. Logs from before
+    /// version 2 were timed from when logging started instead, a little before
+    /// the first frame; their offset is unknown and can be set by hand.
+    pub clock_offset_ms: i64,
 }
 
 /// Complete event log with metadata
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EventLog {
+    /// Format version, see [`EVENT_LOG_VERSION`]
+    pub version: u32,
     pub metadata: RecordingMetadata,
+    /// Events timestamped in video time, i.e. with paused time removed
     pub events: Vec<RecordedEvent>,
+    pub pauses: Vec<PauseInterval>,
 }
 
-/// Start the event logger
-pub fn start_event_logging(width: u32, height: u32) {
-    let mut log = EVENTS_LOG.lock().unwrap();
-    *log = Some(EventLoggerState {
-        events: Vec::new(),
-        start_time: Instant::now(),
-        device_state: DeviceState::new(),
-        last_mouse_state: MouseState {
-            coords: (0, 0),
-            button_pressed: vec![],
-        },
-        last_sample_time: Instant::now(),
-    });
-    // We effectively store width/height in the state if needed, but for now we'll pass them to stop()
-    println!("Event logging started for {}x{}", width, height);
+/// A stretch of a recording starting at a marker
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chapter {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub title: String,
 }
 
-/// Update the event logger (call periodically during recording)
-pub fn update_event_logging() {
-    let mut log = EVENTS_LOG.lock().unwrap();
-    if let Some(ref mut state) = *log {
-        let mouse = state.device_state.get_mouse();
-        let (x, y) = mouse.coords;
-        let timestamp_ms = state.start_time.elapsed().as_millis() as u64;
-
-        // Detect new click
-        let is_clicking = !mouse.button_pressed.is_empty();
-        let was_clicking = !state.last_mouse_state.button_pressed.is_empty();
-
-        if is_clicking && !was_clicking {
-            state
-                .events
-                .push(RecordedEvent::Click { x, y, timestamp_ms });
+impl EventLog {
+    /// Chapters starting at each marker, the last one ending at `duration_ms`
+    ///
+    /// Video before the first marker is a chapter of its own, since players
+    /// expect chapters to cover the whole video. Empty without markers.
+    pub fn chapters(&self, duration_ms: u64) -> Vec<Chapter> {
+        let mut chapters: Vec<Chapter> = Vec::new();
+        for event in &self.events {
+            let RecordedEvent::Marker {
+                label,
+                timestamp_ms,
+            } = event
+            else {
+                continue;
+            };
+            // Chapters can't be empty or run past the end
+            let start_ms = *timestamp_ms;
+            if start_ms >= duration_ms || chapters.last().is_some_and(|c| start_ms <= c.start_ms) {
+                continue;
+            }
+            if chapters.is_empty() && start_ms > 0 {
+                chapters.push(Chapter {
+                    start_ms: 0,
+                    end_ms: start_ms,
+                    title: "Start".to_string(),
+                });
+            }
+            if let Some(last) = chapters.last_mut() {
+                last.end_ms = start_ms;
+            }
+            chapters.push(Chapter {
+                start_ms,
+                end_ms: duration_ms,
+                title: label.clone(),
+            });
         }
+        chapters
+    }
 
-        // Sample cursor position every 16ms (60Hz) for smoother 4K tracking
-        if state.last_sample_time.elapsed() >= Duration::from_millis(16) {
-            state
+    /// The part of the log from `from_ms` to `to_ms`, shifted to start at zero
+    pub fn slice(&self, from_ms: u64, to_ms: u64) -> EventLog {
+        EventLog {
+            version: self.version,
+            metadata: RecordingMetadata {
+                // Approximate if there were pauses before `from_ms`
+                started_at_ms: match self.metadata.started_at_ms {
+                    0 => 0,
+                    started_at_ms => started_at_ms + from_ms,
+                },
+                ..self.metadata.clone()
+            },
+            events: self
                 .events
-                .push(RecordedEvent::CursorMove { x, y, timestamp_ms });
-            state.last_sample_time = Instant::now();
+                .iter()
+                .filter(|e| (from_ms..to_ms).contains(&e.timestamp_ms()))
+                .cloned()
+                .map(|mut e| {
+                    *e.timestamp_ms_mut() -= from_ms;
+                    e
+                })
+                .collect(),
+            pauses: self
+                .pauses
+                .iter()
+                .filter(|p| (from_ms..to_ms).contains(&p.at_ms))
+                .map(|p| PauseInterval {
+                    at_ms: p.at_ms - from_ms,
+                    duration_ms: p.duration_ms,
+                })
+                .collect(),
         }
-
-        state.last_mouse_state = mouse;
     }
 }
 
-/// Stop event logging and get events
-pub fn stop_event_logging() -> Vec<RecordedEvent> {
-    let mut log = EVENTS_LOG.lock().unwrap();
-    if let Some(state) = log.take() {
-        println!(
-            "Event logging stopped. {} events recorded.",
-            state.events.len()
-        );
-        state.events
-    } else {
-        Vec::new()
+/// Start the event logger for a recording described by `metadata`
+///
+/// Events are recorded relative to the captured area in `metadata`, so a
+/// window capture lines up with its own video frames. Input is sampled
+/// `sample_hz` times a second, and key events are stored as `redaction` allows.
+pub fn start_event_logging(
+    metadata: RecordingMetadata,
+    sample_hz: u32,
+    redaction: RedactionPolicy,
+) {
+    send(Command::Start {
+        metadata,
+        sample_hz,
+        redaction,
+    });
+}
+
+/// Journal events to `path` as they are logged, so a crash doesn't lose them
+///
+/// The file is created with the log's metadata; see [`recover_event_journal`].
+pub fn set_event_journal(path: &Path) -> std::io::Result<()> {
+    let (reply, result) = mpsc::channel();
+    send(Command::SetJournal(path.to_path_buf(), reply));
+    result.recv().unwrap_or(Ok(()))
+}
+
+/// Rebuild an event log from a journal left behind by an interrupted recording
+///
+/// Reading stops at the first damaged line, which is where the app died.
+pub fn recover_event_journal(path: &Path) -> Result<EventLog, Box<dyn std::error::Error>> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut metadata = None;
+    let mut events = Vec::new();
+    let mut pauses = Vec::new();
+    for line in file.lines() {
+        let Ok(entry) = serde_json::from_str::<JournalEntry>(&line?) else {
+            break;
+        };
+        match entry {
+            JournalEntry::Metadata(m) => metadata = Some(m),
+            JournalEntry::Event(event) => events.push(event),
+            JournalEntry::Pause(pause) => pauses.push(pause),
+        }
     }
+    Ok(EventLog {
+        version: EVENT_LOG_VERSION,
+        metadata: metadata.ok_or("Event journal has no metadata")?,
+        events,
+        pauses,
+    })
+}
+
+/// Keep only the last `retention` of events (`None` keeps everything)
+pub fn set_event_retention(retention: Option<Duration>) {
+    send(Command::SetRetention(retention));
 }
 
-/// Save event log to JSON file
+/// Copy the events between `from_ms` and `to_ms` without stopping the logger
+///
+/// Timestamps are shifted so `from_ms` becomes zero, matching a video cut
+/// from the same stretch of the timeline. `None` if the logger isn't running.
+pub fn snapshot_event_log(from_ms: u64, to_ms: u64) -> Option<EventLog> {
+    let (reply, log) = mpsc::channel();
+    send(Command::Snapshot {
+        from_ms,
+        to_ms,
+        reply,
+    });
+    log.recv().ok().flatten()
+}
+
+/// Add a chapter marker named `label` at the current moment
+pub fn add_marker(label: String) {
+    send(Command::Marker(label));
+}
+
+/// Pause event logging; nothing is recorded until [`resume_event_logging`]
+pub fn pause_event_logging() {
+    send(Command::Pause);
+}
+
+/// Resume event logging, cutting the paused time out of later timestamps
+pub fn resume_event_logging() {
+    send(Command::Resume);
+}
+
+/// Stop event logging and collect the log; `None` if the logger wasn't running
+///
+/// A pause still open at this point needs no interval: the video just ends.
+pub fn stop_event_logging() -> Option<EventLog> {
+    let (reply, log) = mpsc::channel();
+    send(Command::Stop(reply));
+    log.recv().ok().flatten()
+}
+
+/// Save event log in the compact binary format (see [`super::event_file`])
 pub fn save_event_log(log: &EventLog, path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    write_binary_event_log(log, path)?;
+    Ok(())
+}
+
+/// Export event log as JSON, for tools that don't read the binary format
+pub fn export_event_log_json(
+    log: &EventLog,
+    path: &PathBuf,
+) -> Result<(), Box<dyn std::error::Error>> {
     let json = serde_json::to_string_pretty(log)?;
     std::fs::write(path, json)?;
     Ok(())
 }
 
-/// Load event log from JSON file
+/// Overwrite the event log at `path` with `log`, keeping the file's format
+///
+/// The log is written next to the file and swapped in, so a failed write
+/// keeps the original.
+pub fn replace_event_log(log: &EventLog, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    if is_binary_event_log(path)? {
+        save_event_log(log, &temp)?;
+    } else {
+        export_event_log_json(log, &temp)?;
+    }
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// Set the clock offset of the saved event log at `path`
+///
+/// For recordings whose events run early or late against the video; see
+/// [`RecordingMetadata::clock_offset_ms`].
+pub fn set_event_clock_offset(
+    path: &Path,
+    offset_ms: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut log = load_event_log(&path.to_path_buf())?;
+    log.metadata.clock_offset_ms = offset_ms;
+    replace_event_log(&log, path)
+}
+
+/// Load event log from a binary or JSON file, migrating logs saved by older versions
 pub fn load_event_log(path: &PathBuf) -> Result<EventLog, Box<dyn std::error::Error>> {
+    if is_binary_event_log(path)? {
+        return Ok(read_binary_event_log(path)?);
+    }
     let json = std::fs::read_to_string(path)?;
-    // Try loading as new EventLog format first
-    if let Ok(log) = serde_json::from_str::<EventLog>(&json) {
-        return Ok(log);
+    let log = migrate(serde_json::from_str(&json)?)?;
+    Ok(serde_json::from_value(log)?)
+}
+
+/// Format version of a parsed event log file
+fn schema_version(log: &Value) -> Result<u32, Box<dyn std::error::Error>> {
+    match log {
+        Value::Array(_) => Ok(0),
+        Value::Object(fields) => match fields.get("version") {
+            None => Ok(1),
+            Some(version) => Ok(version
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or("Event log version is not a number")?),
+        },
+        _ => Err("Not an event log".into()),
     }
-    // Fallback for old format (just Vec<RecordedEvent>)
-    let events: Vec<RecordedEvent> = serde_json::from_str(&json)?;
-    Ok(EventLog {
-        metadata: RecordingMetadata {
-            width: 1920,
-            height: 1080,
-        }, // Default for old recordings
-        events,
+}
+
+/// Bring a parsed event log up to [`EVENT_LOG_VERSION`], one version at a time
+fn migrate(mut log: Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut version = schema_version(&log)?;
+    if version > EVENT_LOG_VERSION {
+        return Err(format!(
+            "Event log format version {version} is newer than this app supports \
+             ({EVENT_LOG_VERSION}); update DemoRecorder to open it"
+        )
+        .into());
+    }
+    while version < EVENT_LOG_VERSION {
+        log = match version {
+            0 => migrate_v0(log),
+            1 => migrate_v1(log)?,
+            2 => migrate_v2(log)?,
+            3 => migrate_v3(log)?,
+            4 => migrate_v4(log)?,
+            _ => unreachable!("every older version has a migration"),
+        };
+        version += 1;
+    }
+    Ok(log)
+}
+
+/// 0 -> 1: wrap the bare event list
+///
+/// The captured area wasn't recorded; its size is left unknown (0) so
+/// processing takes it from the video instead of guessing.
+fn migrate_v0(events: Value) -> Value {
+    serde_json::json!({
+        "metadata": { "width": 0, "height": 0, "origin_x": 0, "origin_y": 0 },
+        "events": events,
+        "pauses": [],
     })
 }
+
+/// 1 -> 2: add the version field and the newer metadata as unknown
+fn migrate_v1(mut log: Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let fields = log.as_object_mut().ok_or("Not an event log")?;
+    fields.insert("version".into(), 2.into());
+    fields
+        .entry("pauses")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    let metadata = fields
+        .get_mut("metadata")
+        .and_then(Value::as_object_mut)
+        .ok_or("Event log has no metadata")?;
+    metadata.entry("origin_x").or_insert(0.into());
+    metadata.entry("origin_y").or_insert(0.into());
+    metadata.entry("scale_factor").or_insert(1.0.into());
+    metadata.entry("fps").or_insert(0.into());
+    metadata.entry("app_version").or_insert("".into());
+    metadata.entry("started_at_ms").or_insert(0.into());
+    Ok(log)
+}
+
+/// 2 -> 3: `ActiveWindow` events were added; older logs just have none
+fn migrate_v2(mut log: Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let fields = log.as_object_mut().ok_or("Not an event log")?;
+    fields.insert("version".into(), 3.into());
+    Ok(log)
+}
+
+/// 3 -> 4: `Marker` events were added; older logs just have none
+fn migrate_v3(mut log: Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let fields = log.as_object_mut().ok_or("Not an event log")?;
+    fields.insert("version".into(), 4.into());
+    Ok(log)
+}
+
+/// 4 -> 5: add the capture delay as unknown and the clock offset; logs from
+/// before it are taken as aligned
+fn migrate_v4(mut log: Value) -> Result<Value, Box<dyn std::error::Error>> {
+    let fields = log.as_object_mut().ok_or("Not an event log")?;
+    fields.insert("version".into(), 5.into());
+    let metadata = fields
+        .get_mut("metadata")
+        .and_then(Value::as_object_mut)
+        .ok_or("Event log has no metadata")?;
+    metadata.entry("capture_delay_ms").or_insert(0.into());
+    metadata.entry("clock_offset_ms").or_insert(0.into());
+    Ok(log)
+}
+
+/// Bring the metadata of a binary log written at format `version` up to date
+///
+/// Binary logs store the metadata as JSON, so it goes through the same
+/// migrations as a JSON log.
+pub(super) fn migrate_metadata(
+    metadata: Value,
+    version: u32,
+) -> Result<RecordingMetadata, Box<dyn std::error::Error>> {
+    let log = migrate(serde_json::json!({
+        "version": version,
+        "metadata": metadata,
+        "events": [],
+        "pauses": [],
+    }))?;
+    Ok(serde_json::from_value(log["metadata"].clone())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse and migrate a JSON event log the way `load_event_log` does
+    fn load(json: &str) -> EventLog {
+        let log = migrate(serde_json::from_str(json).unwrap()).unwrap();
+        serde_json::from_value(log).unwrap()
+    }
+
+    fn pauses(log: &EventLog) -> Vec<(u64, u64)> {
+        log.pauses
+            .iter()
+            .map(|p| (p.at_ms, p.duration_ms))
+            .collect()
+    }
+
+    #[test]
+    fn migrates_v0_event_list() {
+        let log = load(
+            r#"[
+                {"Click": {"x": 10, "y": 20, "timestamp_ms": 100}},
+                {"CursorMove": {"x": 11, "y": 21, "timestamp_ms": 150}}
+            ]"#,
+        );
+        assert_eq!(log.version, EVENT_LOG_VERSION);
+        assert_eq!((log.metadata.width, log.metadata.height), (0, 0));
+        assert_eq!(log.metadata.scale_factor, 1.0);
+        assert_eq!(log.metadata.clock_offset_ms, 0);
+        assert!(log.pauses.is_empty());
+        assert_eq!(
+            log.events,
+            [
+                RecordedEvent::Click {
+                    x: 10,
+                    y: 20,
+                    timestamp_ms: 100,
+                    button: MouseButton::Left,
+                },
+                RecordedEvent::CursorMove {
+                    x: 11,
+                    y: 21,
+                    timestamp_ms: 150,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn migrates_v1_wrapper() {
+        let log = load(
+            r#"{
+                "metadata": {"width": 1280, "height": 720},
+                "events": [
+                    {"KeyDown": {"key": "KeyA", "modifiers": {"ctrl": true, "shift": false, "alt": false, "meta": false}, "timestamp_ms": 5}},
+                    {"KeyUp": {"key": "KeyA", "modifiers": {"ctrl": false, "shift": false, "alt": false, "meta": false}, "timestamp_ms": 80}}
+                ]
+            }"#,
+        );
+        assert_eq!(log.version, EVENT_LOG_VERSION);
+        assert_eq!((log.metadata.width, log.metadata.height), (1280, 720));
+        assert_eq!((log.metadata.origin_x, log.metadata.origin_y), (0, 0));
+        assert_eq!(log.metadata.fps, 0);
+        assert_eq!(log.metadata.started_at_ms, 0);
+        assert_eq!(log.metadata.capture_delay_ms, 0);
+        assert_eq!(log.metadata.clock_offset_ms, 0);
+        assert!(log.pauses.is_empty());
+        assert_eq!(
+            log.events,
+            [
+                RecordedEvent::KeyDown {
+                    key: "KeyA".into(),
+                    modifiers: Modifiers {
+                        ctrl: true,
+                        ..Default::default()
+                    },
+                    timestamp_ms: 5,
+                },
+                RecordedEvent::KeyUp {
+                    key: "KeyA".into(),
+                    modifiers: Modifiers::default(),
+                    timestamp_ms: 80,
+                },
+            ]
+        );
+    }
+
+    /// Metadata as written from version 2 until the clock offset was added
+    const V2_METADATA: &str = r#"{
+        "width": 1920, "height": 1080, "origin_x": 1920, "origin_y": 0,
+        "scale_factor": 1.25, "fps": 60, "app_version": "0.1.0",
+        "started_at_ms": 1700000000000
+    }"#;
+
+    #[test]
+    fn migrates_v2_keeps_metadata_and_pauses() {
+        let log = load(&format!(
+            r#"{{
+                "version": 2,
+                "metadata": {V2_METADATA},
+                "events": [{{"Click": {{"x": 1, "y": 2, "timestamp_ms": 3, "button": "Right"}}}}],
+                "pauses": [{{"at_ms": 1000, "duration_ms": 500}}]
+            }}"#
+        ));
+        assert_eq!(log.version, EVENT_LOG_VERSION);
+        assert_eq!(log.metadata.origin_x, 1920);
+        assert_eq!(log.metadata.scale_factor, 1.25);
+        assert_eq!(log.metadata.started_at_ms, 1_700_000_000_000);
+        assert_eq!(log.metadata.capture_delay_ms, 0);
+        assert_eq!(log.metadata.clock_offset_ms, 0);
+        assert_eq!(pauses(&log), [(1000, 500)]);
+        assert_eq!(
+            log.events,
+            [RecordedEvent::Click {
+                x: 1,
+                y: 2,
+                timestamp_ms: 3,
+                button: MouseButton::Right,
+            }]
+        );
+    }
+
+    #[test]
+    fn migrates_v3_active_window() {
+        let log = load(&format!(
+            r#"{{
+                "version": 3,
+                "metadata": {V2_METADATA},
+                "events": [{{"ActiveWindow": {{"title": "Editor", "process": "code", "x": 0, "y": 0, "width": 800, "height": 600, "timestamp_ms": 0}}}}],
+                "pauses": []
+            }}"#
+        ));
+        assert_eq!(log.version, EVENT_LOG_VERSION);
+        assert_eq!(log.metadata.clock_offset_ms, 0);
+        assert!(matches!(
+            &log.events[..],
+            [RecordedEvent::ActiveWindow { title, width: 800, .. }] if title == "Editor"
+        ));
+    }
+
+    #[test]
+    fn migrates_v4_adds_capture_delay_and_clock_offset() {
+        let log = load(&format!(
+            r#"{{
+                "version": 4,
+                "metadata": {V2_METADATA},
+                "events": [{{"Marker": {{"label": "Intro", "timestamp_ms": 2000}}}}],
+                "pauses": [{{"at_ms": 10, "duration_ms": 20}}]
+            }}"#
+        ));
+        assert_eq!(log.version, EVENT_LOG_VERSION);
+        assert_eq!(log.metadata.capture_delay_ms, 0);
+        assert_eq!(log.metadata.clock_offset_ms, 0);
+        assert_eq!(pauses(&log), [(10, 20)]);
+        assert_eq!(
+            log.events,
+            [RecordedEvent::Marker {
+                label: "Intro".into(),
+                timestamp_ms: 2000,
+            }]
+        );
+    }
+
+    #[test]
+    fn current_version_is_untouched() {
+        let json = serde_json::json!({
+            "version": EVENT_LOG_VERSION,
+            "metadata": { "width": 640, "height": 480, "origin_x": 0, "origin_y": 0,
+                "scale_factor": 1.0, "fps": 30, "app_version": "", "started_at_ms": 0,
+                "capture_delay_ms": 120, "clock_offset_ms": -30 },
+            "events": [],
+            "pauses": [],
+        });
+        assert_eq!(migrate(json.clone()).unwrap(), json);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let json = serde_json::json!({
+            "version": EVENT_LOG_VERSION + 1,
+            "metadata": {},
+            "events": [],
+            "pauses": [],
+        });
+        let err = migrate(json).unwrap_err();
+        assert!(err.to_string().contains("newer"), "{err}");
+    }
+
+    #[test]
+    fn rejects_non_logs() {
+        assert!(migrate(serde_json::json!("events")).is_err());
+        assert!(migrate(serde_json::json!({ "version": "two" })).is_err());
+    }
+
+    #[test]
+    fn migrates_binary_metadata() {
+        let metadata = migrate_metadata(serde_json::from_str(V2_METADATA).unwrap(), 2).unwrap();
+        assert_eq!(metadata.fps, 60);
+        assert_eq!(metadata.capture_delay_ms, 0);
+        assert_eq!(metadata.clock_offset_ms, 0);
+    }
+}
@@ -1,8 +1,13 @@
 pub mod diagnostics;
+pub mod event_file;
 pub mod event_log;
 pub mod post_process;
+pub mod redaction;
 pub mod render_engine;
+pub mod scroll;
 
 // camera module kept for Phase 4 AI zoom features
+pub use event_file::{event_log_path, find_event_log, read_event_log_summary, EVENT_LOG_EXTENSION};
 pub use event_log::*;
 pub use post_process::*;
+pub use redaction::{redact_event_log, RedactionPolicy};
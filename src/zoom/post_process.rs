@@ -2,8 +2,9 @@
 //!
 //! Uses video-rs for frame-by-frame processing with zoom/pan effects
 
-use super::event_log::{EventLog, RecordedEvent};
+use super::event_log::{EventLog, MouseButton, RecordedEvent};
 use super::render_engine::{RenderEngine, RenderUniforms};
+use crate::config::{Config, ZoomMode, ZoomTriggers};
 use std::path::Path;
 
 /// Configuration for post-processing
@@ -19,12 +20,50 @@ pub struct PostProcessConfig {
     pub height: u32,
     /// Frame rate
     pub fps: u32,
-    /// Zoom level to apply on clicks (e.g., 1.5 = 150%)
+    /// How the camera moves: zooming in on `triggers` and back out, or
+    /// tracking the cursor at a constant zoom
+    pub mode: ZoomMode,
+    /// Zoom level to apply on clicks, or to hold while following (e.g., 1.5 = 150%)
     pub zoom_level: f32,
-    /// Duration of zoom in/out animation in seconds
+    /// Time set aside for zooming in and for zooming out, in seconds; how fast
+    /// the camera actually moves is up to the springs
     pub zoom_duration: f32,
     /// How long to hold the zoom before zooming out (seconds)
     pub hold_duration: f32,
+    /// What zooms in when not following the cursor
+    pub triggers: ZoomTriggers,
+    /// Clicks needed in a cluster before it is zoomed on (1 zooms on every click)
+    pub cluster_min_clicks: usize,
+    /// Longest gap between clicks of one cluster, in seconds
+    pub cluster_gap_secs: f32,
+    /// Largest share of the recording (in either direction) one cluster may span
+    pub cluster_max_span: f32,
+    /// How far (share of the recording) the cursor may drift while resting
+    pub settle_radius: f32,
+    /// How long the cursor must rest to be zoomed on, in seconds
+    pub settle_secs: f32,
+    /// How far (share of the recording) the cursor must have travelled to a
+    /// resting spot, so a cursor that was idle all along doesn't count
+    pub settle_min_travel: f32,
+    /// Cursor speed (recordings per second) that counts as a fast move
+    pub fast_move_speed: f32,
+    /// Distance (share of the recording) a fast move must cover to zoom back
+    /// out; 0 never zooms out for cursor moves
+    pub fast_move_distance: f32,
+    /// Milliseconds added to event timestamps to get video time, replacing
+    /// the log's own offset (for old recordings whose events run early or late)
+    pub clock_offset_ms: Option<i64>,
+    /// Length of the video in milliseconds, if known; a cursor rest that is
+    /// still going when the log ends lasts until then
+    pub duration_ms: Option<u64>,
+    /// Share of the visible area around its middle where the cursor can move
+    /// without the camera following (0 follows every move)
+    pub follow_dead_zone: f32,
+    /// Pull of the camera springs towards their target; higher moves faster
+    pub camera_stiffness: f32,
+    /// Damping ratio of the camera springs: 1 settles fastest without
+    /// overshooting, lower values bounce, higher ones creep
+    pub camera_damping: f32,
 }
 
 impl Default for PostProcessConfig {
@@ -35,9 +74,37 @@ impl Default for PostProcessConfig {
             width: 1920,
             height: 1080,
             fps: 30,
+            mode: ZoomMode::ClickToZoom,
+            triggers: ZoomTriggers::Clicks,
             zoom_level: 1.5,
             zoom_duration: 0.3,
             hold_duration: 2.0,
+            cluster_min_clicks: 2,
+            cluster_gap_secs: 3.0,
+            cluster_max_span: 0.4,
+            settle_radius: 0.03,
+            settle_secs: 0.6,
+            settle_min_travel: 0.15,
+            fast_move_speed: 2.0,
+            fast_move_distance: 0.4,
+            clock_offset_ms: None,
+            duration_ms: None,
+            follow_dead_zone: 0.5,
+            camera_stiffness: 150.0,
+            camera_damping: 1.0,
+        }
+    }
+}
+
+impl PostProcessConfig {
+    /// Build a config from the user's settings; paths and video properties
+    /// are left to fill in
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            mode: config.zoom_mode,
+            zoom_level: config.zoom_level,
+            triggers: config.zoom_triggers,
+            ..Default::default()
         }
     }
 }
@@ -55,6 +122,49 @@ pub struct ZoomKeyframe {
     pub center_y: f32,
     /// Zoom level
     pub zoom: f32,
+    /// How long to hold the zoom before zooming out (seconds)
+    pub hold_duration: f32,
+}
+
+/// Offset from a right-click to the middle of the context menu it opens, in
+/// recorded pixels (menus open below and to the right of the cursor)
+const CONTEXT_MENU_OFFSET: (f32, f32) = (100.0, 120.0);
+
+/// Scroll events closer together than this (seconds) are one scroll-through
+const SCROLL_GAP_SECS: f32 = 0.5;
+
+/// Switching to a window that fills more than this share of the recording
+/// (in either direction) isn't worth framing
+const WINDOW_FRAME_MAX_SHARE: f32 = 0.9;
+
+/// Clicks closer together than this (seconds) and [`RAPID_CLICK_DISTANCE`]
+/// are one interaction, like the clicks of a double-click
+const RAPID_CLICK_SECS: f32 = 0.4;
+
+/// How close (as a share of the recording's size, along each axis) a rapid
+/// click must be to the previous one to count as the same interaction
+const RAPID_CLICK_DISTANCE: f32 = 0.02;
+
+/// Room left around a drag or click cluster when fitting it into the view
+const FIT_MARGIN: f32 = 1.25;
+
+/// Seconds added to event times to get video time
+fn clock_offset_secs(log: &EventLog, config: &PostProcessConfig) -> f32 {
+    config
+        .clock_offset_ms
+        .unwrap_or(log.metadata.clock_offset_ms) as f32
+        / 1000.0
+}
+
+/// Whether the press of `button` that came right before `rest` turned into a drag
+fn press_becomes_drag(rest: &[RecordedEvent], button: MouseButton) -> bool {
+    rest.iter()
+        .find_map(|event| match event {
+            RecordedEvent::DragStart { button: b, .. } if *b == button => Some(true),
+            RecordedEvent::MouseUp { button: b, .. } if *b == button => Some(false),
+            _ => None,
+        })
+        .unwrap_or(false)
 }
 
 /// Generate zoom keyframes from recorded events
@@ -67,22 +177,185 @@ pub fn generate_keyframes(log: &EventLog, config: &PostProcessConfig) -> Vec<Zoo
     let screen_width = log.metadata.width as f32;
     let screen_height = log.metadata.height as f32;
 
-    for event in &log.events {
-        if let RecordedEvent::Click { x, y, timestamp_ms } = event {
-            let start_time = *timestamp_ms as f32 / 1000.0;
-            let end_time = start_time + config.hold_duration + config.zoom_duration * 2.0;
+    let mut clicks = Vec::new();
+    let mut scroll_starts = Vec::new();
+    let mut last_scroll = None;
+    let mut last_process = None;
+
+    for (i, event) in log.events.iter().enumerate() {
+        // Position (recorded pixels), zoom and hold of the keyframe for this event
+        let (start_ms, (x, y), zoom, hold_duration) = match event {
+            RecordedEvent::Click {
+                x,
+                y,
+                timestamp_ms,
+                button,
+            } => {
+                // A press that turns into a drag is framed as the drag instead
+                if press_becomes_drag(&log.events[i + 1..], *button) {
+                    continue;
+                }
+                let (x, y) = (*x as f32, *y as f32);
+                match button {
+                    // Clicks are framed in clusters once they are all known
+                    MouseButton::Left | MouseButton::Middle => {
+                        clicks.push((*timestamp_ms, (x, y)));
+                        continue;
+                    }
+                    // A context menu is worth a zoom on its own; frame the
+                    // menu rather than the spot clicked
+                    MouseButton::Right if config.triggers != ZoomTriggers::CursorSettle => (
+                        *timestamp_ms,
+                        (x + CONTEXT_MENU_OFFSET.0, y + CONTEXT_MENU_OFFSET.1),
+                        config.zoom_level,
+                        config.hold_duration,
+                    ),
+                    // Back/forward navigate away; nothing to zoom to
+                    MouseButton::Right | MouseButton::Back | MouseButton::Forward => continue,
+                }
+            }
+            RecordedEvent::DragStart {
+                x,
+                y,
+                button,
+                timestamp_ms,
+            } => {
+                let end = log.events[i + 1..].iter().find_map(|e| match e {
+                    RecordedEvent::DragEnd {
+                        x,
+                        y,
+                        button: b,
+                        timestamp_ms,
+                    } if b == button => Some((*x, *y, *timestamp_ms)),
+                    _ => None,
+                });
+                let Some((end_x, end_y, end_ms)) = end else {
+                    continue;
+                };
+                // Fit the whole dragged span, but never zoom in closer than a click
+                let span_x = (end_x - x).abs() as f32 / screen_width;
+                let span_y = (end_y - y).abs() as f32 / screen_height;
+                let fit_zoom = 1.0 / (span_x.max(span_y) * FIT_MARGIN).max(f32::EPSILON);
+                let zoom = config.zoom_level.min(fit_zoom).max(1.0);
+                let center = ((x + end_x) as f32 / 2.0, (y + end_y) as f32 / 2.0);
+                let dragging_secs = end_ms.saturating_sub(*timestamp_ms) as f32 / 1000.0;
+                (
+                    *timestamp_ms,
+                    center,
+                    zoom,
+                    dragging_secs + config.hold_duration,
+                )
+            }
+            RecordedEvent::ActiveWindow {
+                process,
+                x,
+                y,
+                width,
+                height,
+                timestamp_ms,
+                ..
+            } => {
+                // Frame the app switched to; the first window is just where the recording began
+                let switched = last_process.is_some_and(|last| last != process);
+                last_process = Some(process);
+                if !switched {
+                    continue;
+                }
+                // Only the part of the window inside the recording can be framed
+                let x0 = (*x as f32).max(0.0);
+                let y0 = (*y as f32).max(0.0);
+                let x1 = (*x as f32 + *width as f32).min(screen_width);
+                let y1 = (*y as f32 + *height as f32).min(screen_height);
+                let share = ((x1 - x0) / screen_width).max((y1 - y0) / screen_height);
+                if x1 <= x0 || y1 <= y0 || share > WINDOW_FRAME_MAX_SHARE {
+                    continue;
+                }
+                let zoom = config.zoom_level.min(1.0 / share).max(1.0);
+                (
+                    *timestamp_ms,
+                    ((x0 + x1) / 2.0, (y0 + y1) / 2.0),
+                    zoom,
+                    config.hold_duration,
+                )
+            }
+            RecordedEvent::Scroll { timestamp_ms, .. } => {
+                let time = *timestamp_ms as f32 / 1000.0;
+                if last_scroll.is_none_or(|last| time - last > SCROLL_GAP_SECS) {
+                    scroll_starts.push(time);
+                }
+                last_scroll = Some(time);
+                continue;
+            }
+            _ => continue,
+        };
+
+        keyframes.push(framing_keyframe(
+            start_ms,
+            (x, y),
+            zoom,
+            hold_duration,
+            (screen_width, screen_height),
+            config,
+        ));
+    }
+
+    let screen = (screen_width, screen_height);
+    if config.triggers != ZoomTriggers::CursorSettle {
+        keyframes.extend(click_cluster_keyframes(&clicks, screen, config));
+    }
+    let cursor = cursor_samples(log, screen);
+    if config.triggers != ZoomTriggers::Clicks {
+        // A cursor resting at the end logs no more samples, so its rest runs
+        // until the video (or at least the log) ends
+        let log_end_ms = log
+            .events
+            .iter()
+            .map(RecordedEvent::timestamp_ms)
+            .max()
+            .unwrap_or(0);
+        let end_ms = config.duration_ms.map_or(log_end_ms, |video_ms| {
+            let offset_ms = (clock_offset_secs(log, config) * 1000.0) as i64;
+            log_end_ms.max((video_ms as i64 - offset_ms).max(0) as u64)
+        });
+        keyframes.extend(settle_keyframes(&cursor, end_ms, screen, config));
+    }
 
-            // Normalize coordinates to 0-1 range based on RECORDING dimensions
-            let center_x = *x as f32 / screen_width;
-            let center_y = *y as f32 / screen_height;
+    // Content moves under the camera while scrolling, so a scroll-through
+    // zooms back out instead of holding on a spot that has scrolled away.
+    // A fast sweep of the cursor across the recording is leaving too.
+    let zoom_outs = scroll_starts
+        .iter()
+        .copied()
+        .chain(fast_move_starts(&cursor, config));
+    for at in zoom_outs {
+        for keyframe in keyframes.iter_mut() {
+            if keyframe.start_time < at && keyframe.end_time > at {
+                // Leave sooner, never later than the keyframe already would
+                keyframe.end_time = keyframe.end_time.min(at + config.zoom_duration);
+                keyframe.hold_duration = keyframe
+                    .hold_duration
+                    .min((at - keyframe.start_time - config.zoom_duration).max(0.0));
+            }
+        }
+    }
 
-            keyframes.push(ZoomKeyframe {
-                start_time,
-                end_time,
-                center_x: center_x.clamp(0.0, 1.0),
-                center_y: center_y.clamp(0.0, 1.0),
-                zoom: config.zoom_level,
-            });
+    // A zoom shouldn't carry on across a pause, where the video jumps ahead
+    for pause in &log.pauses {
+        let at = pause.at_ms as f32 / 1000.0;
+        for keyframe in keyframes.iter_mut() {
+            if keyframe.start_time < at && keyframe.end_time > at {
+                keyframe.end_time = at;
+            }
+        }
+    }
+
+    // Move from the log's timeline onto the video's
+    let offset = clock_offset_secs(log, config);
+    if offset != 0.0 {
+        keyframes.retain(|keyframe| keyframe.end_time + offset > 0.0);
+        for keyframe in keyframes.iter_mut() {
+            keyframe.start_time = (keyframe.start_time + offset).max(0.0);
+            keyframe.end_time += offset;
         }
     }
 
@@ -98,6 +371,245 @@ pub fn generate_keyframes(log: &EventLog, config: &PostProcessConfig) -> Vec<Zoo
     keyframes
 }
 
+/// Keyframe zooming in on `center` (recorded pixels) at `start_ms` and
+/// holding for `hold_duration` seconds
+fn framing_keyframe(
+    start_ms: u64,
+    (x, y): (f32, f32),
+    zoom: f32,
+    hold_duration: f32,
+    (screen_width, screen_height): (f32, f32),
+    config: &PostProcessConfig,
+) -> ZoomKeyframe {
+    let start_time = start_ms as f32 / 1000.0;
+    let end_time = start_time + hold_duration + config.zoom_duration * 2.0;
+
+    // Normalize coordinates to 0-1 range based on RECORDING dimensions
+    ZoomKeyframe {
+        start_time,
+        end_time,
+        center_x: (x / screen_width).clamp(0.0, 1.0),
+        center_y: (y / screen_height).clamp(0.0, 1.0),
+        zoom,
+        hold_duration,
+    }
+}
+
+/// Clicks that belong together: close in time and inside an area small
+/// enough to zoom into
+struct ClickCluster {
+    start_ms: u64,
+    /// Time of the latest click
+    end_ms: u64,
+    /// Bounding box of the clicks, in recorded pixels
+    min: (f32, f32),
+    max: (f32, f32),
+    /// Latest click, to spot rapid repeats of it
+    last: (f32, f32),
+    /// Separate interactions; rapid clicks on one spot count once
+    interactions: usize,
+}
+
+impl ClickCluster {
+    fn new(ms: u64, point: (f32, f32)) -> Self {
+        Self {
+            start_ms: ms,
+            end_ms: ms,
+            min: point,
+            max: point,
+            last: point,
+            interactions: 1,
+        }
+    }
+
+    /// Whether a click at `point` at `ms` can join: soon enough after the
+    /// latest click, and keeping the cluster small enough to frame
+    fn accepts(
+        &self,
+        ms: u64,
+        (x, y): (f32, f32),
+        (screen_width, screen_height): (f32, f32),
+        config: &PostProcessConfig,
+    ) -> bool {
+        let gap = ms.saturating_sub(self.end_ms) as f32 / 1000.0;
+        let span_x = (self.max.0.max(x) - self.min.0.min(x)) / screen_width;
+        let span_y = (self.max.1.max(y) - self.min.1.min(y)) / screen_height;
+        gap <= config.cluster_gap_secs && span_x.max(span_y) <= config.cluster_max_span
+    }
+
+    fn add(&mut self, ms: u64, (x, y): (f32, f32), (screen_width, screen_height): (f32, f32)) {
+        let gap = ms.saturating_sub(self.end_ms) as f32 / 1000.0;
+        let moved_x = (x - self.last.0).abs() / screen_width;
+        let moved_y = (y - self.last.1).abs() / screen_height;
+        let moved = moved_x.max(moved_y);
+        if gap > RAPID_CLICK_SECS || moved > RAPID_CLICK_DISTANCE {
+            self.interactions += 1;
+        }
+        self.end_ms = ms;
+        self.min = (self.min.0.min(x), self.min.1.min(y));
+        self.max = (self.max.0.max(x), self.max.1.max(y));
+        self.last = (x, y);
+    }
+}
+
+/// Group `clicks` (time and recorded position) into clusters and frame the
+/// ones with enough interactions to be worth a zoom
+///
+/// Only left and middle clicks are clustered; a right-click is framed on its
+/// own, so a lone one still zooms to its context menu.
+///
+/// A cluster's keyframe fits all its clicks, zooming no closer than a single
+/// click would, and holds until `hold_duration` after its last click.
+fn click_cluster_keyframes(
+    clicks: &[(u64, (f32, f32))],
+    screen: (f32, f32),
+    config: &PostProcessConfig,
+) -> Vec<ZoomKeyframe> {
+    let mut clusters: Vec<ClickCluster> = Vec::new();
+    for &(ms, point) in clicks {
+        match clusters.last_mut() {
+            Some(cluster) if cluster.accepts(ms, point, screen, config) => {
+                cluster.add(ms, point, screen)
+            }
+            _ => clusters.push(ClickCluster::new(ms, point)),
+        }
+    }
+
+    clusters
+        .into_iter()
+        .filter(|cluster| cluster.interactions >= config.cluster_min_clicks.max(1))
+        .map(|cluster| {
+            let span_x = (cluster.max.0 - cluster.min.0) / screen.0;
+            let span_y = (cluster.max.1 - cluster.min.1) / screen.1;
+            let fit_zoom = 1.0 / (span_x.max(span_y) * FIT_MARGIN).max(f32::EPSILON);
+            let center = (
+                (cluster.min.0 + cluster.max.0) / 2.0,
+                (cluster.min.1 + cluster.max.1) / 2.0,
+            );
+            let clicking_secs = cluster.end_ms.saturating_sub(cluster.start_ms) as f32 / 1000.0;
+            framing_keyframe(
+                cluster.start_ms,
+                center,
+                config.zoom_level.min(fit_zoom).max(1.0),
+                clicking_secs + config.hold_duration,
+                screen,
+                config,
+            )
+        })
+        .collect()
+}
+
+/// Cursor positions over time: timestamp and position as a share of the
+/// recording
+fn cursor_samples(
+    log: &EventLog,
+    (screen_width, screen_height): (f32, f32),
+) -> Vec<(u64, f32, f32)> {
+    log.events
+        .iter()
+        .filter_map(|event| {
+            let (x, y) = event.position()?;
+            Some((
+                event.timestamp_ms(),
+                x as f32 / screen_width,
+                y as f32 / screen_height,
+            ))
+        })
+        .collect()
+}
+
+fn distance((_, x0, y0): (u64, f32, f32), (_, x1, y1): (u64, f32, f32)) -> f32 {
+    (x1 - x0).hypot(y1 - y0)
+}
+
+/// Keyframes for spots the cursor came to rest on after travelling there
+///
+/// The cursor rests while it stays within `settle_radius` of where it
+/// stopped. A rest of at least `settle_secs` after `settle_min_travel` of
+/// movement is zoomed on from the moment the cursor arrived until it leaves,
+/// or until `end_ms` if it never does.
+fn settle_keyframes(
+    cursor: &[(u64, f32, f32)],
+    end_ms: u64,
+    screen: (f32, f32),
+    config: &PostProcessConfig,
+) -> Vec<ZoomKeyframe> {
+    let mut keyframes = Vec::new();
+    let Some(&first) = cursor.first() else {
+        return keyframes;
+    };
+    // Where the cursor stopped, and the latest sample still near it
+    let mut anchor = first;
+    let mut last_inside = first;
+    // Distance travelled since the previous rest
+    let mut approach = 0.0;
+
+    let ends = cursor[1..].iter().map(Some).chain([None]);
+    for sample in ends {
+        if let Some(&sample) = sample {
+            if distance(anchor, sample) <= config.settle_radius {
+                last_inside = sample;
+                continue;
+            }
+        }
+
+        // The cursor left the spot (or is still on it at the end): frame it
+        // if it rested there
+        let rest_end_ms = match sample {
+            Some(_) => last_inside.0,
+            None => last_inside.0.max(end_ms),
+        };
+        let rest_secs = rest_end_ms.saturating_sub(anchor.0) as f32 / 1000.0;
+        let rested = rest_secs >= config.settle_secs;
+        if rested && approach >= config.settle_min_travel {
+            keyframes.push(framing_keyframe(
+                anchor.0,
+                (anchor.1 * screen.0, anchor.2 * screen.1),
+                config.zoom_level,
+                rest_secs,
+                screen,
+                config,
+            ));
+        }
+        let Some(&sample) = sample else {
+            break;
+        };
+        if rested {
+            approach = 0.0;
+        }
+        approach += distance(anchor, sample);
+        anchor = sample;
+        last_inside = sample;
+    }
+    keyframes
+}
+
+/// Times (seconds) at which the cursor starts a fast move that goes on for
+/// at least `fast_move_distance`
+fn fast_move_starts(cursor: &[(u64, f32, f32)], config: &PostProcessConfig) -> Vec<f32> {
+    let mut starts = Vec::new();
+    if config.fast_move_distance <= 0.0 {
+        return starts;
+    }
+    // Start and length of the fast move in progress, and whether it was counted
+    let mut run: Option<(u64, f32, bool)> = None;
+    for pair in cursor.windows(2) {
+        let secs = pair[1].0.saturating_sub(pair[0].0) as f32 / 1000.0;
+        let step = distance(pair[0], pair[1]);
+        if secs <= 0.0 || step / secs < config.fast_move_speed {
+            run = None;
+            continue;
+        }
+        let (start, length, counted) = run.get_or_insert((pair[0].0, 0.0, false));
+        *length += step;
+        if !*counted && *length >= config.fast_move_distance {
+            *counted = true;
+            starts.push(*start as f32 / 1000.0);
+        }
+    }
+    starts
+}
+
 /// Adjust keyframes so they don't overlap, creating a sequential path
 fn merge_overlapping_keyframes(keyframes: &mut Vec<ZoomKeyframe>) {
     if keyframes.len() < 2 {
@@ -118,53 +630,44 @@ fn merge_overlapping_keyframes(keyframes: &mut Vec<ZoomKeyframe>) {
 }
 
 /// Finds the nearest cursor position at a given time from the event log with linear interpolation
-fn get_cursor_pos_at(time_secs: f32, log: &EventLog) -> (f32, f32) {
-    if log.events.is_empty() {
-        return (0.5, 0.5);
-    }
-
+///
+/// Positions are not interpolated across a pause, where the video cuts.
+/// `time_secs` is video time; the log's clock offset is taken off first.
+fn get_cursor_pos_at(time_secs: f32, log: &EventLog, config: &PostProcessConfig) -> (f32, f32) {
+    let time_secs = time_secs - clock_offset_secs(log, config);
     let screen_width = log.metadata.width as f32;
     let screen_height = log.metadata.height as f32;
 
-    // Find bounding events for interpolation
+    // Find bounding events for interpolation, among those with a cursor position
     let mut prev_event = None;
     let mut next_event = None;
 
-    for event in &log.events {
-        let timestamp_ms = match event {
-            RecordedEvent::Click { timestamp_ms, .. } => *timestamp_ms,
-            RecordedEvent::CursorMove { timestamp_ms, .. } => *timestamp_ms,
-        };
-        let event_time = timestamp_ms as f32 / 1000.0;
-
+    let positions = log.events.iter().filter_map(|event| {
+        let time = event.timestamp_ms() as f32 / 1000.0;
+        event.position().map(|pos| (pos, time))
+    });
+    for (pos, event_time) in positions {
         if event_time <= time_secs {
-            prev_event = Some(event);
+            prev_event = Some((pos, event_time));
         } else {
-            next_event = Some(event);
+            next_event = Some((pos, event_time));
             break;
         }
     }
 
     match (prev_event, next_event) {
-        (Some(p), Some(n)) => {
-            let (px, py, pt) = match p {
-                RecordedEvent::Click { x, y, timestamp_ms } => {
-                    (*x, *y, *timestamp_ms as f32 / 1000.0)
-                }
-                RecordedEvent::CursorMove { x, y, timestamp_ms } => {
-                    (*x, *y, *timestamp_ms as f32 / 1000.0)
-                }
-            };
-            let (nx, ny, nt) = match n {
-                RecordedEvent::Click { x, y, timestamp_ms } => {
-                    (*x, *y, *timestamp_ms as f32 / 1000.0)
-                }
-                RecordedEvent::CursorMove { x, y, timestamp_ms } => {
-                    (*x, *y, *timestamp_ms as f32 / 1000.0)
-                }
-            };
-
-            if nt > pt {
+        (Some(((px, py), pt)), Some(((nx, ny), nt))) => {
+            // Jump straight to the next position if the video cuts in between
+            let cut = log
+                .pauses
+                .iter()
+                .map(|pause| pause.at_ms as f32 / 1000.0)
+                .find(|&at| at > pt && at <= nt);
+
+            if let Some(at) = cut {
+                let (x, y) = if time_secs < at { (px, py) } else { (nx, ny) };
+                (x as f32 / screen_width, y as f32 / screen_height)
+            } else if nt > pt {
                 let t = (time_secs - pt) / (nt - pt);
                 let x = px as f32 + (nx as f32 - px as f32) * t;
                 let y = py as f32 + (ny as f32 - py as f32) * t;
@@ -173,18 +676,7 @@ fn get_cursor_pos_at(time_secs: f32, log: &EventLog) -> (f32, f32) {
                 (px as f32 / screen_width, py as f32 / screen_height)
             }
         }
-        (Some(p), None) => {
-            let (x, y) = match p {
-                RecordedEvent::Click { x, y, .. } => (*x, *y),
-                RecordedEvent::CursorMove { x, y, .. } => (*x, *y),
-            };
-            (x as f32 / screen_width, y as f32 / screen_height)
-        }
-        (None, Some(n)) => {
-            let (x, y) = match n {
-                RecordedEvent::Click { x, y, .. } => (*x, *y),
-                RecordedEvent::CursorMove { x, y, .. } => (*x, *y),
-            };
+        (Some(((x, y), _)), None) | (None, Some(((x, y), _))) => {
             (x as f32 / screen_width, y as f32 / screen_height)
         }
         (None, None) => (0.5, 0.5),
@@ -202,113 +694,248 @@ pub struct CameraState {
     pub mouse_cy: f32,
 }
 
-/// Calculate camera state at a given time using "Magnetic Camera" interpolation
-fn calculate_camera_at_time(
-    time_secs: f32,
-    keyframes: &[ZoomKeyframe],
-    log: &EventLog,
-    config: &PostProcessConfig,
-) -> CameraState {
-    // Returns detailed camera state
-
-    // 1. Find indices of previous, current, and next keyframes
-    let mut current_idx = None;
-    for (i, kf) in keyframes.iter().enumerate() {
-        if time_secs >= kf.start_time && time_secs <= kf.end_time {
-            current_idx = Some(i);
-            break;
-        }
-    }
+/// Where the camera should be at some moment; the springs take it there
+struct CameraTarget {
+    zoom: f32,
+    cx: f32,
+    cy: f32,
+    mouse_cx: f32,
+    mouse_cy: f32,
+}
 
-    if let Some(idx) = current_idx {
-        let kf = &keyframes[idx];
-        let zoom_in_end = kf.start_time + config.zoom_duration;
+/// Simulation step of the camera springs, in seconds
+///
+/// Smaller than any frame interval, so the motion doesn't depend on the
+/// frame rate.
+const SPRING_STEP: f32 = 1.0 / 240.0;
+
+/// One camera coordinate, pulled towards its target by a damped spring
+#[derive(Clone, Copy)]
+struct Spring {
+    value: f32,
+    velocity: f32,
+}
 
-        // Ensure hold/zoom-out doesn't exceed the keyframe duration (which might be cut short by next click)
-        let hold_end = (zoom_in_end + config.hold_duration).min(kf.end_time);
-
-        // Zoom Interpolation
-        let zoom = if time_secs < zoom_in_end {
-            let t = (time_secs - kf.start_time) / config.zoom_duration;
-            let eased_t = ease_in_out(t);
-            // Zoom in from 1.0 to Target
-            1.0 + (kf.zoom - 1.0) * eased_t
-        } else if time_secs < hold_end {
-            kf.zoom
-        } else {
-            // Zoom out back to 1.0
-            let t = ((time_secs - hold_end) / config.zoom_duration).clamp(0.0, 1.0);
-            let eased_t = ease_in_out(t);
-            kf.zoom - (kf.zoom - 1.0) * eased_t
-        };
+impl Spring {
+    fn new(value: f32) -> Self {
+        Self {
+            value,
+            velocity: 0.0,
+        }
+    }
 
-        // Center Interpolation (Panning)
-        // If this is not the first keyframe, pan from the previous click center
-        let (start_cx, start_cy) = if idx > 0 {
-            (keyframes[idx - 1].center_x, keyframes[idx - 1].center_y)
-        } else {
-            (0.5, 0.5)
-        };
+    /// Advance by `dt` seconds towards `target`
+    ///
+    /// `damping` is the damping ratio: 1 is critically damped, reaching the
+    /// target as fast as possible without overshooting it.
+    fn step(&mut self, target: f32, dt: f32, stiffness: f32, damping: f32) {
+        let friction = 2.0 * damping * stiffness.sqrt();
+        let acceleration = stiffness * (target - self.value) - friction * self.velocity;
+        // Semi-implicit Euler: stable for stiff springs at small steps
+        self.velocity += acceleration * dt;
+        self.value += self.velocity * dt;
+    }
 
-        // The pan should start as soon as the keyframe begins
-        let pan_t = ((time_secs - kf.start_time) / config.zoom_duration).clamp(0.0, 1.0);
-        let eased_pan_t = ease_in_out(pan_t);
+    fn snap(&mut self, value: f32) {
+        self.value = value;
+        self.velocity = 0.0;
+    }
 
-        let mut cx = start_cx + (kf.center_x - start_cx) * eased_pan_t;
-        let mut cy = start_cy + (kf.center_y - start_cy) * eased_pan_t;
+    /// Stop at `min` or `max` if the spring has gone past it
+    fn clamp(&mut self, min: f32, max: f32) {
+        let clamped = self.value.clamp(min, max);
+        if clamped != self.value {
+            self.snap(clamped);
+        }
+    }
+}
 
-        let (mouse_cx, mouse_cy) = get_cursor_pos_at(time_secs, log);
+/// The camera, simulated frame by frame over the whole video
+///
+/// Zoom and centre each follow their target on a spring, so the camera
+/// keeps its momentum when the target changes mid-move instead of jumping
+/// between easing curves. Frames must be fed in order.
+struct SpringCamera {
+    zoom: Spring,
+    cx: Spring,
+    cy: Spring,
+    /// Video time of the previous frame; `None` before the first
+    last_time: Option<f32>,
+}
 
-        // "Cursor Follow" - Magnetic drift towards the live cursor during the hold phase
-        if time_secs > zoom_in_end && time_secs < hold_end {
-            // Apply a softer, progressive magnetic pull towards the cursor
-            // We use a time-based blend to avoid the frame-1 jump
-            let hold_t = (time_secs - zoom_in_end) / (hold_end - zoom_in_end);
-            let follow_weight = (hold_t * 5.0).min(1.0) * 0.35; // Fade in the follow effect over 200ms
+impl SpringCamera {
+    fn new() -> Self {
+        Self {
+            zoom: Spring::new(1.0),
+            cx: Spring::new(0.5),
+            cy: Spring::new(0.5),
+            last_time: None,
+        }
+    }
 
-            cx = cx + (mouse_cx - cx) * follow_weight;
-            cy = cy + (mouse_cy - cy) * follow_weight;
+    /// Move the camera on to `time_secs`, heading for `target`
+    ///
+    /// Jumps straight to the target at the first frame and across a pause,
+    /// where the video cuts.
+    fn update(
+        &mut self,
+        time_secs: f32,
+        target: CameraTarget,
+        log: &EventLog,
+        config: &PostProcessConfig,
+    ) -> CameraState {
+        match self.last_time {
+            Some(last) if !cut_between(last, time_secs, log, config) => {
+                let mut remaining = (time_secs - last).max(0.0);
+                while remaining > 0.0 {
+                    let dt = remaining.min(SPRING_STEP);
+                    let (k, d) = (config.camera_stiffness, config.camera_damping);
+                    self.zoom.step(target.zoom, dt, k, d);
+                    self.cx.step(target.cx, dt, k, d);
+                    self.cy.step(target.cy, dt, k, d);
+                    remaining -= dt;
+                }
+            }
+            _ => {
+                self.zoom.snap(target.zoom);
+                self.cx.snap(target.cx);
+                self.cy.snap(target.cy);
+            }
         }
+        // The centre lags the zoom on its own spring; never let that show
+        // past the edge of the recording
+        let half = 0.5 / self.zoom.value.max(1.0);
+        self.cx.clamp(half, 1.0 - half);
+        self.cy.clamp(half, 1.0 - half);
+        self.last_time = Some(time_secs);
 
         tracing::debug!(
             time = %format!("{:.3}", time_secs),
-            zoom = %format!("{:.2}", zoom),
-            center = %format!("{:.3},{:.3}", cx, cy),
+            zoom = %format!("{:.2}", self.zoom.value),
+            center = %format!("{:.3},{:.3}", self.cx.value, self.cy.value),
             "Camera State"
         );
 
-        return CameraState {
+        CameraState {
+            zoom: self.zoom.value.max(1.0),
+            cx: self.cx.value,
+            cy: self.cy.value,
+            target_cx: target.cx,
+            target_cy: target.cy,
+            mouse_cx: target.mouse_cx,
+            mouse_cy: target.mouse_cy,
+        }
+    }
+
+    /// Target for [`ZoomMode::FollowCursor`]: a constant zoom that keeps the
+    /// cursor in view
+    ///
+    /// The camera stays put while the cursor moves inside the dead zone around
+    /// the middle of the view, and heads for the spot that puts the cursor
+    /// back on the dead zone's edge when it leaves.
+    fn follow_cursor_target(
+        &self,
+        time_secs: f32,
+        log: &EventLog,
+        config: &PostProcessConfig,
+    ) -> CameraTarget {
+        let zoom = config.zoom_level.max(1.0);
+        let (mouse_cx, mouse_cy) = get_cursor_pos_at(time_secs, log, config);
+        // Before the first frame the camera starts wherever the cursor is
+        let (cx, cy) = match self.last_time {
+            Some(_) => (self.cx.value, self.cy.value),
+            None => (mouse_cx, mouse_cy),
+        };
+        CameraTarget {
             zoom,
-            cx,
-            cy,
-            target_cx: kf.center_x,
-            target_cy: kf.center_y,
+            cx: keep_in_view(cx, mouse_cx, zoom, config.follow_dead_zone),
+            cy: keep_in_view(cy, mouse_cy, zoom, config.follow_dead_zone),
             mouse_cx,
             mouse_cy,
+        }
+    }
+
+    /// Where the keyframes want the camera at a given time
+    ///
+    /// A keyframe zooms in at its start, holds (keeping the cursor in view)
+    /// and zooms back out `zoom_duration` before its end; the springs smooth
+    /// every change.
+    fn keyframe_target(
+        &self,
+        time_secs: f32,
+        keyframes: &[ZoomKeyframe],
+        log: &EventLog,
+        config: &PostProcessConfig,
+    ) -> CameraTarget {
+        let (mouse_cx, mouse_cy) = get_cursor_pos_at(time_secs, log, config);
+        let active = keyframes
+            .iter()
+            .find(|kf| time_secs >= kf.start_time && time_secs <= kf.end_time);
+        let Some(kf) = active else {
+            return self.zoom_out_target(mouse_cx, mouse_cy);
         };
+
+        // Ensure hold/zoom-out doesn't exceed the keyframe duration (which might be cut short by next click)
+        let hold_end = (kf.start_time + config.zoom_duration + kf.hold_duration).min(kf.end_time);
+        if time_secs >= hold_end {
+            return self.zoom_out_target(mouse_cx, mouse_cy);
+        }
+
+        // Once zoomed in, drift along with a cursor leaving the middle of the view
+        let holding = time_secs >= kf.start_time + config.zoom_duration;
+        let (mut cx, mut cy) = (kf.center_x, kf.center_y);
+        if holding {
+            cx = keep_in_view(cx, mouse_cx, kf.zoom, config.follow_dead_zone);
+            cy = keep_in_view(cy, mouse_cy, kf.zoom, config.follow_dead_zone);
+        }
+        // Never aim past what the zoomed view can show
+        let half = 0.5 / kf.zoom.max(1.0);
+        CameraTarget {
+            zoom: kf.zoom,
+            cx: cx.clamp(half, 1.0 - half),
+            cy: cy.clamp(half, 1.0 - half),
+            mouse_cx,
+            mouse_cy,
+        }
     }
 
-    // No zoom active - return to center slowly if we just finished a keyframe
-    // TODO: Implement "return to center" panning
-    CameraState {
-        zoom: 1.0,
-        cx: 0.5,
-        cy: 0.5,
-        target_cx: 0.5,
-        target_cy: 0.5,
-        mouse_cx: 0.5,
-        mouse_cy: 0.5,
+    /// Target while no zoom is held: all the way out, returning to the middle
+    ///
+    /// The centre stays where it is and is only pulled in as far as the
+    /// widening view needs to stay inside the recording (which
+    /// [`update`](Self::update) enforces). Zooming out and recentring are then
+    /// one movement, arriving at the middle exactly when the zoom reaches 1,
+    /// instead of the view sliding sideways on its own.
+    fn zoom_out_target(&self, mouse_cx: f32, mouse_cy: f32) -> CameraTarget {
+        let half = 0.5 / self.zoom.value.max(1.0);
+        CameraTarget {
+            zoom: 1.0,
+            cx: self.cx.value.clamp(half, 1.0 - half),
+            cy: self.cy.value.clamp(half, 1.0 - half),
+            mouse_cx,
+            mouse_cy,
+        }
     }
 }
 
-/// Smooth ease in/out curve
-fn ease_in_out(t: f32) -> f32 {
-    // Cubic easing for smoother transitions
-    if t < 0.5 {
-        4.0 * t * t * t
-    } else {
-        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
-    }
+/// Centre coordinate closest to `center` that keeps `mouse` within
+/// `dead_zone` of the middle of the view at `zoom`, and the view inside the
+/// recording
+fn keep_in_view(center: f32, mouse: f32, zoom: f32, dead_zone: f32) -> f32 {
+    let half = 0.5 / zoom.max(1.0);
+    let free = half * dead_zone.clamp(0.0, 1.0);
+    center
+        .clamp(mouse - free, mouse + free)
+        .clamp(half, 1.0 - half)
+}
+
+/// Whether the video cuts (at a pause) after `from_secs` and by `to_secs`
+fn cut_between(from_secs: f32, to_secs: f32, log: &EventLog, config: &PostProcessConfig) -> bool {
+    let offset = clock_offset_secs(log, config);
+    log.pauses.iter().any(|pause| {
+        let at = pause.at_ms as f32 / 1000.0 + offset;
+        at > from_secs && at <= to_secs
+    })
 }
 
 // apply_zoom_to_frame removed in favor of RenderEngine
@@ -351,24 +978,73 @@ pub fn apply_zoom_effects(
     actual_config.width = width as u32;
     actual_config.height = height as u32;
     actual_config.fps = frame_rate as u32;
+    if actual_config.duration_ms.is_none() {
+        actual_config.duration_ms = crate::capture::ffmpeg::probe_duration_ms(source);
+    }
+
+    // Logs from before the captured size was recorded have events in video pixels
+    let sized_log;
+    let log = if log.metadata.width == 0 || log.metadata.height == 0 {
+        let mut copy = log.clone();
+        copy.metadata.width = width as u32;
+        copy.metadata.height = height as u32;
+        sized_log = copy;
+        &sized_log
+    } else {
+        log
+    };
 
-    // NOW generate keyframes with actual dimensions
-    let keyframes = generate_keyframes(log, &actual_config);
+    // Real capture times of the frames, if the recorder left a stats sidecar;
+    // more accurate than container timestamps when frames were dropped
+    let frame_times_ms =
+        crate::capture::stats::CaptureStats::load(&crate::capture::stats::stats_path(source))
+            .map(|stats| stats.frame_times_ms)
+            .unwrap_or_default();
     writeln!(
         audit_log,
-        "[KEYFRAMES] Generated {} keyframes from {} events",
-        keyframes.len(),
-        log.events.len()
+        "[TIMING] {} capture timestamps from sidecar",
+        frame_times_ms.len()
     )?;
-    println!(
-        "Generated {} keyframes from {} events",
-        keyframes.len(),
-        log.events.len()
-    );
 
-    if keyframes.is_empty() {
-        println!("No keyframes to apply, copying file...");
+    // Following the cursor needs no keyframes, just a zoom to follow at
+    let following = match config.mode {
+        ZoomMode::None => return Err("Zoom is turned off in Settings".into()),
+        ZoomMode::ClickToZoom | ZoomMode::SmartAI => false,
+        ZoomMode::FollowCursor => true,
+    };
+    let keyframes = if following {
+        writeln!(
+            audit_log,
+            "[FOLLOW] Following the cursor at {:.2}x",
+            config.zoom_level
+        )?;
+        Vec::new()
+    } else {
+        // NOW generate keyframes with actual dimensions
+        let keyframes = generate_keyframes(log, &actual_config);
+        writeln!(
+            audit_log,
+            "[KEYFRAMES] Generated {} keyframes from {} events",
+            keyframes.len(),
+            log.events.len()
+        )?;
+        println!(
+            "Generated {} keyframes from {} events",
+            keyframes.len(),
+            log.events.len()
+        );
+        keyframes
+    };
+
+    let nothing_to_do = if following {
+        config.zoom_level <= 1.0
+    } else {
+        keyframes.is_empty()
+    };
+    if nothing_to_do {
+        println!("Nothing to zoom, copying file...");
         std::fs::copy(&config.input_path, &config.output_path)?;
+        add_marker_chapters(log, Path::new(&config.output_path), &mut audit_log)?;
         return Ok(());
     }
 
@@ -378,6 +1054,9 @@ pub fn apply_zoom_effects(
     let mut encoder = Encoder::new(destination, settings)?;
 
     let mut processed = 0;
+    // Position in the stream, counting frames that failed to decode, which
+    // still took their slot in the capture timestamps
+    let mut frame_index = 0;
 
     println!("Starting frame processing...");
 
@@ -389,6 +1068,7 @@ pub fn apply_zoom_effects(
         width, height
     )?;
 
+    let mut camera = SpringCamera::new();
     let mut current_zoom = 1.0;
     let mut current_cx = 0.5;
     let mut current_cy = 0.5;
@@ -415,18 +1095,29 @@ pub fn apply_zoom_effects(
                     println!("End of video stream at frame {}", processed);
                     break;
                 }
-                println!("Error decoding frame {}: {}", processed, e);
+                println!("Error decoding frame {}: {}", frame_index, e);
+                frame_index += 1;
                 continue;
             }
         };
-        let time_secs = time.as_secs_f64() as f32;
+        let index = frame_index;
+        frame_index += 1;
+        let time_secs = match frame_times_ms.get(index) {
+            Some(&ms) => ms as f32 / 1000.0,
+            None => time.as_secs_f64() as f32,
+        };
 
         // Calculate camera state at this time
-        let state = calculate_camera_at_time(time_secs, &keyframes, log, config);
+        let target = if following {
+            camera.follow_cursor_target(time_secs, log, config)
+        } else {
+            camera.keyframe_target(time_secs, &keyframes, log, config)
+        };
+        let state = camera.update(time_secs, target, log, config);
 
         // Record telemetry
         telemetry.frames.push(TelemetryFrame {
-            frame_index: processed,
+            frame_index: index,
             time_secs,
             zoom: state.zoom,
             cx: state.cx,
@@ -525,6 +1216,17 @@ pub fn apply_zoom_effects(
     // Finish encoding
     encoder.finish()?;
 
+    // The encoder is video-only; bring the recording's audio track back
+    match crate::capture::audio::copy_audio(source, destination) {
+        Ok(()) => writeln!(audit_log, "[AUDIO] Copied audio from input")?,
+        Err(e) => {
+            tracing::warn!("Zoomed video has no audio: {}", e);
+            writeln!(audit_log, "[AUDIO] Not copied: {}", e)?;
+        }
+    }
+
+    add_marker_chapters(log, destination, &mut audit_log)?;
+
     tracing::info!("Post-processing complete. Processed {} frames.", processed);
 
     // Save telemetry to logs folder
@@ -539,6 +1241,30 @@ pub fn apply_zoom_effects(
     Ok(())
 }
 
+/// Embed the log's markers in `video` as chapters
+///
+/// Failing to add them is logged but doesn't fail the processing.
+fn add_marker_chapters(
+    log: &EventLog,
+    video: &Path,
+    audit_log: &mut std::fs::File,
+) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let duration_ms = crate::capture::ffmpeg::probe_duration_ms(video).unwrap_or(0);
+    let chapters = log.chapters(duration_ms);
+    if chapters.is_empty() {
+        return Ok(());
+    }
+    match crate::capture::ffmpeg::add_chapters(video, &chapters) {
+        Ok(()) => writeln!(audit_log, "[CHAPTERS] Added {} chapters", chapters.len()),
+        Err(e) => {
+            tracing::warn!("Zoomed video has no chapters: {}", e);
+            writeln!(audit_log, "[CHAPTERS] Not added: {}", e)
+        }
+    }
+}
+
 // Keep the old generate_ffmpeg functions for reference but unused
 #[allow(dead_code)]
 pub fn generate_ffmpeg_command(log: &EventLog, config: &PostProcessConfig) -> String {
@@ -564,3 +1290,424 @@ fn generate_ffmpeg_filter(_keyframes: &[ZoomKeyframe], _config: &PostProcessConf
     // Deprecated - kept for reference
     String::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: (f32, f32) = (1920.0, 1080.0);
+
+    /// A 1920x1080 log of `events`, without pauses
+    fn log(events: Vec<RecordedEvent>) -> EventLog {
+        EventLog {
+            version: crate::zoom::EVENT_LOG_VERSION,
+            metadata: crate::zoom::RecordingMetadata {
+                width: 1920,
+                height: 1080,
+                origin_x: 0,
+                origin_y: 0,
+                scale_factor: 1.0,
+                fps: 30,
+                app_version: String::new(),
+                started_at_ms: 0,
+                capture_delay_ms: 0,
+                clock_offset_ms: 0,
+            },
+            events,
+            pauses: Vec::new(),
+        }
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn separated_clicks_form_two_clusters() {
+        let clicks = [
+            (0, (200.0, 200.0)),
+            (1_000, (260.0, 220.0)),
+            (10_000, (1500.0, 800.0)),
+            (11_000, (1550.0, 850.0)),
+        ];
+        let keyframes = click_cluster_keyframes(&clicks, SCREEN, &PostProcessConfig::default());
+
+        assert_eq!(keyframes.len(), 2);
+        assert_near(keyframes[0].start_time, 0.0);
+        assert_near(keyframes[0].center_x, 230.0 / 1920.0);
+        assert_near(keyframes[0].center_y, 210.0 / 1080.0);
+        assert_near(keyframes[1].start_time, 10.0);
+        assert_near(keyframes[1].center_x, 1525.0 / 1920.0);
+        assert_near(keyframes[1].center_y, 825.0 / 1080.0);
+    }
+
+    #[test]
+    fn double_click_is_one_interaction() {
+        let config = PostProcessConfig::default();
+        let clicks = [(0, (500.0, 500.0)), (150, (502.0, 501.0))];
+        assert!(click_cluster_keyframes(&clicks, SCREEN, &config).is_empty());
+
+        let mut cluster = ClickCluster::new(0, (500.0, 500.0));
+        cluster.add(150, (502.0, 501.0), SCREEN);
+        assert_eq!(cluster.interactions, 1);
+        // A slow second click on the same spot is a new interaction
+        cluster.add(1_000, (502.0, 501.0), SCREEN);
+        assert_eq!(cluster.interactions, 2);
+    }
+
+    #[test]
+    fn rapid_click_distance_uses_each_axis() {
+        // 30px is 1.6% of the width but 2.8% of the height: a different row
+        let mut cluster = ClickCluster::new(0, (500.0, 500.0));
+        cluster.add(150, (500.0, 530.0), SCREEN);
+        assert_eq!(cluster.interactions, 2);
+
+        let mut cluster = ClickCluster::new(0, (500.0, 500.0));
+        cluster.add(150, (530.0, 500.0), SCREEN);
+        assert_eq!(cluster.interactions, 1);
+    }
+
+    #[test]
+    fn cluster_wider_than_max_span_splits() {
+        let config = PostProcessConfig::default();
+        let clicks = [
+            (0, (100.0, 100.0)),
+            (1_000, (150.0, 120.0)),
+            // Close in time, but framing it with the first two would span
+            // most of the screen
+            (2_000, (1800.0, 100.0)),
+            (3_000, (1750.0, 150.0)),
+        ];
+        let cluster = ClickCluster::new(0, (100.0, 100.0));
+        assert!(cluster.accepts(1_000, (150.0, 120.0), SCREEN, &config));
+        assert!(!cluster.accepts(2_000, (1800.0, 100.0), SCREEN, &config));
+
+        let keyframes = click_cluster_keyframes(&clicks, SCREEN, &config);
+        assert_eq!(keyframes.len(), 2);
+        assert_near(keyframes[0].center_x, 125.0 / 1920.0);
+        assert_near(keyframes[1].start_time, 2.0);
+        assert_near(keyframes[1].center_x, 1775.0 / 1920.0);
+    }
+
+    #[test]
+    fn settles_after_travelling() {
+        let config = PostProcessConfig::default();
+        let cursor = [
+            (0, 0.1, 0.1),
+            (100, 0.3, 0.3),
+            (200, 0.5, 0.5),
+            // Arrives, drifts a little and rests for 0.9s
+            (300, 0.6, 0.6),
+            (600, 0.605, 0.6),
+            (1_200, 0.6, 0.61),
+            (1_300, 0.9, 0.9),
+        ];
+        let keyframes = settle_keyframes(&cursor, 1_300, SCREEN, &config);
+
+        assert_eq!(keyframes.len(), 1);
+        assert_near(keyframes[0].start_time, 0.3);
+        assert_near(keyframes[0].center_x, 0.6);
+        assert_near(keyframes[0].center_y, 0.6);
+        assert_near(keyframes[0].hold_duration, 0.9);
+        assert_near(keyframes[0].zoom, config.zoom_level);
+    }
+
+    #[test]
+    fn idle_cursor_does_not_settle() {
+        let config = PostProcessConfig::default();
+        let cursor = [(0, 0.5, 0.5), (1_000, 0.5, 0.5), (2_000, 0.51, 0.5)];
+        assert!(settle_keyframes(&cursor, 5_000, SCREEN, &config).is_empty());
+
+        // Too short a rest
+        let cursor = [
+            (0, 0.1, 0.1),
+            (100, 0.5, 0.5),
+            (400, 0.5, 0.5),
+            (500, 0.9, 0.9),
+        ];
+        assert!(settle_keyframes(&cursor, 500, SCREEN, &config).is_empty());
+    }
+
+    #[test]
+    fn rest_at_end_of_log_lasts_until_the_end() {
+        let config = PostProcessConfig::default();
+        // The cursor stops and no more samples are logged
+        let cursor = [(0, 0.1, 0.1), (100, 0.5, 0.5)];
+
+        let keyframes = settle_keyframes(&cursor, 3_000, SCREEN, &config);
+        assert_eq!(keyframes.len(), 1);
+        assert_near(keyframes[0].start_time, 0.1);
+        assert_near(keyframes[0].hold_duration, 2.9);
+
+        // Ending right as it stops leaves no time to rest
+        assert!(settle_keyframes(&cursor, 100, SCREEN, &config).is_empty());
+    }
+
+    #[test]
+    fn trailing_rest_uses_video_duration() {
+        let log = log(vec![
+            RecordedEvent::CursorMove {
+                x: 192,
+                y: 108,
+                timestamp_ms: 0,
+            },
+            RecordedEvent::CursorMove {
+                x: 960,
+                y: 540,
+                timestamp_ms: 100,
+            },
+        ]);
+        let mut config = PostProcessConfig {
+            triggers: ZoomTriggers::CursorSettle,
+            ..Default::default()
+        };
+        assert!(generate_keyframes(&log, &config).is_empty());
+
+        config.duration_ms = Some(5_000);
+        let keyframes = generate_keyframes(&log, &config);
+        assert_eq!(keyframes.len(), 1);
+        assert_near(keyframes[0].start_time, 0.1);
+        assert_near(keyframes[0].center_x, 0.5);
+    }
+
+    /// Samples every 10ms from `start_ms`, moving `step` to the right each time
+    fn sweep(start_ms: u64, x: f32, steps: usize, step: f32) -> Vec<(u64, f32, f32)> {
+        (0..=steps)
+            .map(|i| (start_ms + i as u64 * 10, x + i as f32 * step, 0.5))
+            .collect()
+    }
+
+    #[test]
+    fn finds_fast_move_starts() {
+        let config = PostProcessConfig::default();
+        // 3 recordings per second: a fast sweep across 60% of the recording
+        let mut cursor = sweep(0, 0.0, 20, 0.03);
+        // Slow drift back, 0.1 recordings per second
+        cursor.extend(sweep(1_000, 0.6, 10, -0.001));
+        // A quick flick that's too short to count
+        cursor.extend(sweep(1_500, 0.59, 5, 0.03));
+        // Slow again, then a second sweep
+        cursor.extend(sweep(1_800, 0.74, 2, -0.001));
+        cursor.extend(sweep(2_000, 0.7, 15, -0.03));
+
+        let starts = fast_move_starts(&cursor, &config);
+        assert_eq!(starts.len(), 2, "{starts:?}");
+        assert_near(starts[0], 0.0);
+        assert_near(starts[1], 2.0);
+    }
+
+    #[test]
+    fn fast_moves_can_be_disabled() {
+        let config = PostProcessConfig {
+            fast_move_distance: 0.0,
+            ..Default::default()
+        };
+        assert!(fast_move_starts(&sweep(0, 0.0, 20, 0.03), &config).is_empty());
+    }
+
+    /// Frame rate the camera tests are simulated at
+    const FPS: f32 = 60.0;
+
+    fn target(zoom: f32, cx: f32, cy: f32) -> CameraTarget {
+        CameraTarget {
+            zoom,
+            cx,
+            cy,
+            mouse_cx: cx,
+            mouse_cy: cy,
+        }
+    }
+
+    /// Most a spring may move in one frame heading for a target `distance`
+    /// away: a critically damped spring let go at rest peaks at a speed of
+    /// distance * √stiffness / e (with some room for the integration)
+    fn max_frame_step(distance: f32, config: &PostProcessConfig) -> f32 {
+        distance * config.camera_stiffness.sqrt() / std::f32::consts::E / FPS * 1.1
+    }
+
+    #[test]
+    fn camera_pans_between_distant_keyframes_without_overshooting() {
+        let config = PostProcessConfig::default();
+        let log = log(Vec::new());
+        let mut camera = SpringCamera::new();
+        // Zoomed in on the top left, then on the bottom right
+        let mut previous = camera.update(0.0, target(2.0, 0.25, 0.3), &log, &config);
+        let (max_x, max_y) = (max_frame_step(0.5, &config), max_frame_step(0.4, &config));
+
+        for frame in 1..=120 {
+            let time = frame as f32 / FPS;
+            let state = camera.update(time, target(2.0, 0.75, 0.7), &log, &config);
+            assert!(
+                state.cx <= 0.75 && state.cy <= 0.7,
+                "overshot at {time}s: {}, {}",
+                state.cx,
+                state.cy
+            );
+            assert!(
+                state.cx - previous.cx <= max_x && state.cy - previous.cy <= max_y,
+                "too fast at {time}s: {} -> {}, {} -> {}",
+                previous.cx,
+                state.cx,
+                previous.cy,
+                state.cy
+            );
+            assert_near(state.zoom, 2.0);
+            previous = state;
+        }
+        assert_near(previous.cx, 0.75);
+        assert_near(previous.cy, 0.7);
+    }
+
+    #[test]
+    fn camera_zooms_in_without_overshooting() {
+        let config = PostProcessConfig::default();
+        let log = log(Vec::new());
+        let mut camera = SpringCamera::new();
+        let mut previous = camera.update(0.0, target(1.0, 0.5, 0.5), &log, &config);
+        let max_step = max_frame_step(1.0, &config);
+
+        for frame in 1..=120 {
+            let time = frame as f32 / FPS;
+            let state = camera.update(time, target(2.0, 0.5, 0.5), &log, &config);
+            assert!(state.zoom <= 2.0, "overshot at {time}s: {}", state.zoom);
+            assert!(
+                state.zoom - previous.zoom <= max_step,
+                "too fast at {time}s: {} -> {}",
+                previous.zoom,
+                state.zoom
+            );
+            previous = state;
+        }
+        assert_near(previous.zoom, 2.0);
+    }
+
+    #[test]
+    fn zoom_out_recentres_as_the_view_widens() {
+        let config = PostProcessConfig::default();
+        let log = log(Vec::new());
+        let mut camera = SpringCamera::new();
+        // Zoomed in on the bottom left corner
+        camera.update(0.0, target(2.0, 0.25, 0.75), &log, &config);
+        let mut zoomed_out = None;
+
+        for frame in 1..=120 {
+            let time = frame as f32 / FPS;
+            let target = camera.zoom_out_target(0.25, 0.75);
+            let state = camera.update(time, target, &log, &config);
+            let half = 0.5 / state.zoom;
+            for center in [state.cx, state.cy] {
+                assert!(
+                    center - half >= -1e-5 && center + half <= 1.0 + 1e-5,
+                    "view left the recording at {time}s: zoom {}, centre {}, {}",
+                    state.zoom,
+                    state.cx,
+                    state.cy
+                );
+            }
+            if state.zoom <= 1.0 + 1e-4 {
+                zoomed_out.get_or_insert(time);
+                // Back in the middle, and staying there
+                assert!(
+                    (state.cx - 0.5).abs() < 1e-4 && (state.cy - 0.5).abs() < 1e-4,
+                    "centre {}, {} at zoom {} at {time}s",
+                    state.cx,
+                    state.cy,
+                    state.zoom
+                );
+            }
+        }
+        assert!(zoomed_out.is_some(), "never zoomed out");
+    }
+
+    #[test]
+    fn keep_in_view_stays_inside_the_recording() {
+        // The cursor at the edge pulls the centre only as far as the view allows
+        assert_near(keep_in_view(0.5, 0.0, 2.0, 0.5), 0.25);
+        assert_near(keep_in_view(0.5, 1.0, 2.0, 0.5), 0.75);
+        // Inside the dead zone the centre stays put
+        assert_near(keep_in_view(0.5, 0.6, 2.0, 0.5), 0.5);
+        // Fully zoomed out there is only one place to be
+        assert_near(keep_in_view(0.2, 0.1, 1.0, 0.5), 0.5);
+    }
+
+    fn click(x: i32, y: i32, timestamp_ms: u64, button: MouseButton) -> RecordedEvent {
+        RecordedEvent::Click {
+            x,
+            y,
+            timestamp_ms,
+            button,
+        }
+    }
+
+    #[test]
+    fn isolated_right_click_frames_its_menu() {
+        let config = PostProcessConfig::default();
+        assert!(config.cluster_min_clicks >= 2);
+
+        let keyframes = generate_keyframes(
+            &log(vec![click(800, 400, 1_000, MouseButton::Right)]),
+            &config,
+        );
+        assert_eq!(keyframes.len(), 1);
+        assert_near(keyframes[0].start_time, 1.0);
+        assert_near(keyframes[0].center_x, 900.0 / 1920.0);
+        assert_near(keyframes[0].center_y, 520.0 / 1080.0);
+        assert_near(keyframes[0].zoom, config.zoom_level);
+
+        // A lone left click isn't enough for a cluster
+        let keyframes = generate_keyframes(
+            &log(vec![click(800, 400, 1_000, MouseButton::Left)]),
+            &config,
+        );
+        assert!(keyframes.is_empty());
+
+        // Nor is a right-click when only resting cursors zoom
+        let config = PostProcessConfig {
+            triggers: ZoomTriggers::CursorSettle,
+            ..Default::default()
+        };
+        let keyframes = generate_keyframes(
+            &log(vec![click(800, 400, 1_000, MouseButton::Right)]),
+            &config,
+        );
+        assert!(keyframes.is_empty());
+    }
+
+    /// Cursor moves every 10ms from `start_ms`, 58px to the right each time:
+    /// a fast sweep across 60% of the recording
+    fn fast_sweep(start_ms: u64) -> Vec<RecordedEvent> {
+        (0..=20)
+            .map(|i| RecordedEvent::CursorMove {
+                x: 200 + i * 58,
+                y: 540,
+                timestamp_ms: start_ms + i as u64 * 10,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fast_move_cuts_a_hold_short_but_never_extends_it() {
+        let config = PostProcessConfig::default();
+        let right_click = click(800, 400, 1_000, MouseButton::Right);
+        let end_time = 1.0 + config.hold_duration + config.zoom_duration * 2.0;
+
+        // A sweep in the middle of the hold zooms out right away
+        let mut events = vec![right_click.clone()];
+        events.extend(fast_sweep(2_000));
+        let keyframes = generate_keyframes(&log(events), &config);
+        assert_eq!(keyframes.len(), 1);
+        assert_near(keyframes[0].end_time, 2.0 + config.zoom_duration);
+        assert_near(keyframes[0].hold_duration, 1.0 - config.zoom_duration);
+
+        // One while already zooming out doesn't keep the zoom any longer
+        let mut events = vec![right_click];
+        events.extend(fast_sweep(3_500));
+        let keyframes = generate_keyframes(&log(events), &config);
+        assert_eq!(keyframes.len(), 1);
+        assert_near(keyframes[0].end_time, end_time);
+        assert_near(keyframes[0].hold_duration, config.hold_duration);
+    }
+}
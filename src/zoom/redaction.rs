@@ -0,0 +1,426 @@
+//! Keeping typed text out of event logs
+//!
+//! Key events are what shortcut overlays are made from, but the keys someone
+//! types are their text, passwords included. A [`RedactionPolicy`] decides
+//! what of a key press may be stored: shortcuts and special keys (arrows,
+//! F-keys, Escape) keep their names, while keys that type or edit text are
+//! stored only as a category like `"Letter"`, `"Whitespace"` or `"Edit"`, so
+//! word lengths, line breaks and corrections can't be read back either. While
+//! a password prompt or password manager is in front, keys aren't stored at
+//! all, and neither is its title. A window that can't be identified (or has
+//! no title) counts as sensitive too, so keys are only ever stored for a
+//! window known to be safe.
+//!
+//! The logger applies the policy as it samples; [`redact_event_log`] applies
+//! it to a log saved earlier.
+
+use super::event_log::{load_event_log, replace_event_log, EventLog, Modifiers, RecordedEvent};
+use crate::config::{Config, KeyLogging};
+use std::path::Path;
+
+/// Keys that type punctuation, by their stored name
+const SYMBOL_KEYS: &[&str] = &[
+    "Grave",
+    "Minus",
+    "Equal",
+    "LeftBracket",
+    "RightBracket",
+    "BackSlash",
+    "Semicolon",
+    "Apostrophe",
+    "Comma",
+    "Dot",
+    "Slash",
+    "NumpadAdd",
+    "NumpadSubtract",
+    "NumpadMultiply",
+    "NumpadDivide",
+    "NumpadDecimal",
+    "NumpadEquals",
+];
+
+/// Keys that space out typed text, stored as `"Whitespace"`
+const WHITESPACE_KEYS: &[&str] = &["Space", "Tab"];
+
+/// Keys that break or correct typed text, stored as `"Edit"`
+const EDIT_KEYS: &[&str] = &["Enter", "NumpadEnter", "Backspace", "Delete"];
+
+/// What of each key event may be stored
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RedactionPolicy {
+    pub keys: KeyLogging,
+    /// Lowercase words that mark a window as sensitive, matched against its
+    /// title and app name
+    pub sensitive_windows: Vec<String>,
+}
+
+impl RedactionPolicy {
+    /// Build a policy from the user's settings
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            keys: config.key_logging,
+            sensitive_windows: config
+                .sensitive_windows
+                .iter()
+                .map(|word| word.trim().to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Whether keys typed into this window must not be stored
+    ///
+    /// Untitled windows are: prompts often have no title.
+    pub fn is_sensitive(&self, title: &str, process: &str) -> bool {
+        if title.trim().is_empty() {
+            return true;
+        }
+        let title = title.to_lowercase();
+        let process = process.to_lowercase();
+        self.sensitive_windows
+            .iter()
+            .any(|word| title.contains(word.as_str()) || process.contains(word.as_str()))
+    }
+}
+
+/// Category a key is stored as while typing, `None` for keys that don't
+/// type or edit text
+fn key_category(key: &str) -> Option<&'static str> {
+    let digit = key
+        .strip_prefix("Key")
+        .or_else(|| key.strip_prefix("Numpad"));
+    if key.len() == 1 && key.chars().all(|c| c.is_ascii_uppercase()) {
+        Some("Letter")
+    } else if digit.is_some_and(|d| d.len() == 1 && d.chars().all(|c| c.is_ascii_digit())) {
+        Some("Digit")
+    } else if SYMBOL_KEYS.contains(&key) {
+        Some("Symbol")
+    } else if WHITESPACE_KEYS.contains(&key) {
+        Some("Whitespace")
+    } else if EDIT_KEYS.contains(&key) {
+        Some("Edit")
+    } else {
+        None
+    }
+}
+
+/// Whether a character key pressed with `modifiers` types it, rather than
+/// being a shortcut
+///
+/// Ctrl+Alt together is AltGr on many layouts, which types characters too.
+fn is_typing(modifiers: Modifiers) -> bool {
+    !(modifiers.ctrl || modifiers.alt || modifiers.meta) || (modifiers.ctrl && modifiers.alt)
+}
+
+/// Applies a policy to events in the order they happened
+///
+/// Tracks the window in front through `ActiveWindow` events, and which held
+/// keys were typed, so a release is stored under the same name as its press
+/// even when the modifiers changed in between. Until the first `ActiveWindow`
+/// event (and in logs without any) the window is unknown, so keys are dropped.
+pub(super) struct Redactor {
+    policy: RedactionPolicy,
+    /// Whether the window in front is sensitive or unknown
+    sensitive: bool,
+    /// Held keys whose press was stored as a category
+    typed: Vec<String>,
+}
+
+impl Redactor {
+    pub(super) fn new(policy: RedactionPolicy) -> Self {
+        Self {
+            policy,
+            sensitive: true,
+            typed: Vec::new(),
+        }
+    }
+
+    /// The event as it may be stored, or `None` to drop it
+    pub(super) fn redact(&mut self, event: RecordedEvent) -> Option<RecordedEvent> {
+        match event {
+            RecordedEvent::ActiveWindow {
+                title,
+                process,
+                x,
+                y,
+                width,
+                height,
+                timestamp_ms,
+            } => {
+                self.sensitive = self.policy.is_sensitive(&title, &process);
+                Some(RecordedEvent::ActiveWindow {
+                    // The title of a login prompt can name the account
+                    title: if self.sensitive { String::new() } else { title },
+                    process,
+                    x,
+                    y,
+                    width,
+                    height,
+                    timestamp_ms,
+                })
+            }
+            RecordedEvent::KeyDown {
+                key,
+                modifiers,
+                timestamp_ms,
+            } => {
+                let key = self.redact_key(key, is_typing(modifiers))?;
+                Some(RecordedEvent::KeyDown {
+                    key,
+                    modifiers,
+                    timestamp_ms,
+                })
+            }
+            RecordedEvent::KeyUp {
+                key,
+                modifiers,
+                timestamp_ms,
+            } => {
+                let typed = match self.typed.iter().position(|k| *k == key) {
+                    Some(i) => {
+                        self.typed.swap_remove(i);
+                        true
+                    }
+                    None => false,
+                };
+                let key = self.redact_key(key, typed)?;
+                Some(RecordedEvent::KeyUp {
+                    key,
+                    modifiers,
+                    timestamp_ms,
+                })
+            }
+            event => Some(event),
+        }
+    }
+
+    /// Note that the window in front can't be identified; keys are dropped
+    /// until an `ActiveWindow` event says it is safe
+    pub(super) fn window_unknown(&mut self) {
+        self.sensitive = true;
+    }
+
+    /// Name `key` may be stored under; `typing` marks a press that types text
+    fn redact_key(&mut self, key: String, typing: bool) -> Option<String> {
+        if self.sensitive || self.policy.keys == KeyLogging::Off {
+            return None;
+        }
+        match key_category(&key) {
+            Some(category) if typing => {
+                if !self.typed.contains(&key) {
+                    self.typed.push(key);
+                }
+                Some(category.to_string())
+            }
+            _ => Some(key),
+        }
+    }
+}
+
+impl EventLog {
+    /// Apply `policy` to the log's key and window events, returning how many
+    /// were changed or dropped
+    pub fn redact(&mut self, policy: &RedactionPolicy) -> usize {
+        let mut redactor = Redactor::new(policy.clone());
+        let mut changed = 0;
+        self.events = std::mem::take(&mut self.events)
+            .into_iter()
+            .filter_map(|event| {
+                let redacted = redactor.redact(event.clone());
+                if redacted.as_ref() != Some(&event) {
+                    changed += 1;
+                }
+                redacted
+            })
+            .collect();
+        changed
+    }
+}
+
+/// Scrub the saved event log at `path` in place, keeping its format
+///
+/// Returns how many events were changed or dropped. The file is only
+/// rewritten when something changed.
+pub fn redact_event_log(
+    path: &Path,
+    policy: &RedactionPolicy,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let path = path.to_path_buf();
+    let mut log = load_event_log(&path)?;
+    let changed = log.redact(policy);
+    if changed == 0 {
+        return Ok(0);
+    }
+
+    replace_event_log(&log, &path)?;
+    println!("Redacted {changed} events in {}", path.display());
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::event_log::{export_event_log_json, RecordingMetadata, EVENT_LOG_VERSION};
+    use super::*;
+
+    fn policy() -> RedactionPolicy {
+        RedactionPolicy {
+            keys: KeyLogging::Categories,
+            sensitive_windows: vec!["password".to_string()],
+        }
+    }
+
+    fn window(title: &str) -> RecordedEvent {
+        RecordedEvent::ActiveWindow {
+            title: title.to_string(),
+            process: "editor".to_string(),
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+            timestamp_ms: 0,
+        }
+    }
+
+    /// Press and release of each key in turn, with `modifiers` held
+    fn keystrokes(keys: &[&str], modifiers: Modifiers) -> Vec<RecordedEvent> {
+        keys.iter()
+            .enumerate()
+            .flat_map(|(i, key)| {
+                let timestamp_ms = 100 + i as u64 * 100;
+                [
+                    RecordedEvent::KeyDown {
+                        key: key.to_string(),
+                        modifiers,
+                        timestamp_ms,
+                    },
+                    RecordedEvent::KeyUp {
+                        key: key.to_string(),
+                        modifiers,
+                        timestamp_ms: timestamp_ms + 50,
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    /// Names of the stored key events, presses and releases alike
+    fn key_names(events: &[RecordedEvent]) -> Vec<&str> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                RecordedEvent::KeyDown { key, .. } | RecordedEvent::KeyUp { key, .. } => {
+                    Some(key.as_str())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn log(events: Vec<RecordedEvent>) -> EventLog {
+        EventLog {
+            version: EVENT_LOG_VERSION,
+            metadata: RecordingMetadata {
+                width: 800,
+                height: 600,
+                origin_x: 0,
+                origin_y: 0,
+                scale_factor: 1.0,
+                fps: 30,
+                app_version: String::new(),
+                started_at_ms: 0,
+                capture_delay_ms: 0,
+                clock_offset_ms: 0,
+            },
+            events,
+            pauses: Vec::new(),
+        }
+    }
+
+    /// "a b⏎" followed by a correction
+    const TYPED: &[&str] = &["A", "Space", "B", "Enter", "Tab", "Backspace", "Delete"];
+
+    #[test]
+    fn typed_text_is_stored_as_categories() {
+        let mut redactor = Redactor::new(policy());
+        let mut events = vec![redactor.redact(window("notes.txt")).unwrap()];
+        events.extend(
+            keystrokes(TYPED, Modifiers::default())
+                .into_iter()
+                .filter_map(|event| redactor.redact(event)),
+        );
+
+        let stored = key_names(&events);
+        assert_eq!(stored.len(), TYPED.len() * 2);
+        for key in TYPED {
+            assert!(!stored.contains(key), "{key} stored by name: {stored:?}");
+        }
+        assert_eq!(
+            stored.iter().step_by(2).copied().collect::<Vec<_>>(),
+            [
+                "Letter",
+                "Whitespace",
+                "Letter",
+                "Edit",
+                "Whitespace",
+                "Edit",
+                "Edit"
+            ]
+        );
+    }
+
+    #[test]
+    fn shortcuts_keep_their_names() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        let mut redactor = Redactor::new(policy());
+        redactor.redact(window("notes.txt"));
+        let events: Vec<_> = keystrokes(&["S", "Backspace", "Enter"], ctrl)
+            .into_iter()
+            .chain(keystrokes(&["Escape", "F5"], Modifiers::default()))
+            .filter_map(|event| redactor.redact(event))
+            .collect();
+
+        let stored = key_names(&events);
+        assert_eq!(stored.len(), 10);
+        assert_eq!(
+            stored.iter().step_by(2).copied().collect::<Vec<_>>(),
+            ["S", "Backspace", "Enter", "Escape", "F5"]
+        );
+    }
+
+    #[test]
+    fn sensitive_windows_store_no_keys() {
+        let mut redactor = Redactor::new(policy());
+        let shown = redactor.redact(window("Enter password")).unwrap();
+        assert!(matches!(shown, RecordedEvent::ActiveWindow { ref title, .. } if title.is_empty()));
+        let events: Vec<_> = keystrokes(TYPED, Modifiers::default())
+            .into_iter()
+            .filter_map(|event| redactor.redact(event))
+            .collect();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn scrubs_saved_log() {
+        let path = std::env::temp_dir().join(format!(
+            "demo-recorder-redaction-{}.json",
+            std::process::id()
+        ));
+        let mut events = vec![window("notes.txt")];
+        events.extend(keystrokes(TYPED, Modifiers::default()));
+        export_event_log_json(&log(events), &path).unwrap();
+
+        let changed = redact_event_log(&path, &policy()).unwrap();
+        let scrubbed = load_event_log(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(changed, TYPED.len() * 2);
+        let stored = key_names(&scrubbed.events);
+        assert_eq!(stored.len(), TYPED.len() * 2);
+        for key in TYPED {
+            assert!(!stored.contains(key), "{key} left in the log: {stored:?}");
+        }
+    }
+}
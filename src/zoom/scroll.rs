@@ -0,0 +1,117 @@
+//! Global mouse wheel listener
+//!
+//! `device_query` only reports held buttons, and a wheel notch is over before
+//! the next poll, so scrolling needs a platform listener: a low-level mouse
+//! hook on Windows and XInput2 raw button events (buttons 4-7) on X11. Wheel
+//! movement is summed into atomics that the event logger drains.
+
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Once;
+
+/// Wheel movement of one notch, in the units Windows reports
+pub const WHEEL_DELTA: i32 = 120;
+
+/// Horizontal and vertical wheel movement since the last [`take_scroll`]
+///
+/// Positive values scroll right and down.
+static SCROLL_X: AtomicI32 = AtomicI32::new(0);
+static SCROLL_Y: AtomicI32 = AtomicI32::new(0);
+
+static LISTENER: Once = Once::new();
+
+fn add_scroll(dx: i32, dy: i32) {
+    SCROLL_X.fetch_add(dx, Ordering::Relaxed);
+    SCROLL_Y.fetch_add(dy, Ordering::Relaxed);
+}
+
+/// Take the wheel movement since the last call, in [`WHEEL_DELTA`] units
+pub fn take_scroll() -> (i32, i32) {
+    (
+        SCROLL_X.swap(0, Ordering::Relaxed),
+        SCROLL_Y.swap(0, Ordering::Relaxed),
+    )
+}
+
+/// Start listening for wheel movement; later calls do nothing
+pub fn start_scroll_listener() {
+    LISTENER.call_once(|| {
+        std::thread::spawn(|| {
+            if let Err(e) = listen() {
+                eprintln!("Scroll events unavailable: {e}");
+            }
+        });
+    });
+}
+
+#[cfg(windows)]
+fn listen() -> Result<(), Box<dyn std::error::Error>> {
+    use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageW, SetWindowsHookExW, MSG, MSLLHOOKSTRUCT, WH_MOUSE_LL,
+        WM_MOUSEHWHEEL, WM_MOUSEWHEEL,
+    };
+
+    unsafe extern "system" fn mouse_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code >= 0 {
+            let message = wparam.0 as u32;
+            if message == WM_MOUSEWHEEL || message == WM_MOUSEHWHEEL {
+                // SAFETY: for WH_MOUSE_LL, lparam points to an MSLLHOOKSTRUCT
+                let info = unsafe { &*(lparam.0 as *const MSLLHOOKSTRUCT) };
+                let delta = (info.mouseData >> 16) as u16 as i16 as i32;
+                if message == WM_MOUSEWHEEL {
+                    // Positive is away from the user, i.e. scrolling up
+                    add_scroll(0, -delta);
+                } else {
+                    add_scroll(delta, 0);
+                }
+            }
+        }
+        unsafe { CallNextHookEx(None, code, wparam, lparam) }
+    }
+
+    // The hook is called on this thread, which must pump messages for it
+    unsafe {
+        SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook), None, 0)?;
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {}
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn listen() -> Result<(), Box<dyn std::error::Error>> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xinput::{self, ConnectionExt as _};
+    use x11rb::protocol::Event;
+
+    let (conn, screen_num) = x11rb::connect(None)?;
+    let root = conn.setup().roots[screen_num].root;
+    // Asking for 2.0 keeps the server sending wheel notches as buttons 4-7
+    // rather than smooth-scroll valuators
+    conn.xinput_xi_query_version(2, 0)?.reply()?;
+    conn.xinput_xi_select_events(
+        root,
+        &[xinput::EventMask {
+            deviceid: xinput::Device::ALL_MASTER.into(),
+            mask: vec![xinput::XIEventMask::RAW_BUTTON_PRESS.into()],
+        }],
+    )?;
+    conn.flush()?;
+
+    loop {
+        if let Event::XinputRawButtonPress(event) = conn.wait_for_event()? {
+            match event.detail {
+                4 => add_scroll(0, -WHEEL_DELTA),
+                5 => add_scroll(0, WHEEL_DELTA),
+                6 => add_scroll(-WHEEL_DELTA, 0),
+                7 => add_scroll(WHEEL_DELTA, 0),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn listen() -> Result<(), Box<dyn std::error::Error>> {
+    Err("not supported on this platform".into())
+}